@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX pending_action_events_status_idx;
+ALTER TABLE pending_action_events
+    DROP COLUMN status,
+    DROP COLUMN status_timestamps;
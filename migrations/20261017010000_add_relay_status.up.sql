@@ -0,0 +1,13 @@
+-- Add up migration script here
+ALTER TABLE pending_action_events
+    ADD COLUMN status            TEXT  NOT NULL DEFAULT 'observed' CHECK (status <> ''),
+    ADD COLUMN status_timestamps JSONB NOT NULL DEFAULT jsonb_build_object('observed', NOW());
+
+-- relays that were already started before the lifecycle was tracked
+UPDATE pending_action_events
+SET status            = 'relay_started',
+    status_timestamps = status_timestamps || jsonb_build_object('relay_started', NOW())
+WHERE (relay_details ->> 'sent_at') IS NOT NULL
+  AND (relay_details ->> 'sent_at') <> '';
+
+CREATE INDEX pending_action_events_status_idx ON pending_action_events (status);
@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP TRIGGER relay_audit_log_append_only ON relay_audit_log;
+DROP FUNCTION relay_audit_log_append_only();
+DROP TABLE relay_audit_log;
+ALTER TABLE pending_action_events
+    DROP COLUMN status_reason,
+    DROP COLUMN terminated_at_height,
+    DROP COLUMN terminated_by_tx_hash;
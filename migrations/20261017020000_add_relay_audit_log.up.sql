@@ -0,0 +1,35 @@
+-- Add up migration script here
+ALTER TABLE pending_action_events
+    ADD COLUMN status_reason         TEXT,
+    ADD COLUMN terminated_at_height  BIGINT,
+    ADD COLUMN terminated_by_tx_hash TEXT;
+
+CREATE TABLE relay_audit_log
+(
+    id             BIGSERIAL   PRIMARY KEY,
+    nonce          BIGINT,
+    entity         TEXT        NOT NULL CHECK (entity <> ''),
+    entity_id      TEXT        NOT NULL CHECK (entity_id <> ''),
+    from_status    TEXT,
+    to_status      TEXT        NOT NULL CHECK (to_status <> ''),
+    reason         TEXT,
+    carbon_height  BIGINT,
+    carbon_tx_hash TEXT,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX relay_audit_log_nonce_idx ON relay_audit_log (nonce);
+
+-- relay_audit_log is append-only
+CREATE FUNCTION relay_audit_log_append_only() RETURNS TRIGGER AS
+$$
+BEGIN
+    RAISE EXCEPTION 'relay_audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER relay_audit_log_append_only
+    BEFORE UPDATE OR DELETE
+    ON relay_audit_log
+    FOR EACH ROW
+EXECUTE FUNCTION relay_audit_log_append_only();
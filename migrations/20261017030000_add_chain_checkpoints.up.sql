@@ -0,0 +1,7 @@
+-- Add up migration script here
+CREATE TABLE chain_checkpoints
+(
+    chain_id             TEXT        PRIMARY KEY CHECK (chain_id <> ''),
+    last_processed_block BIGINT      NOT NULL,
+    updated_at           TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
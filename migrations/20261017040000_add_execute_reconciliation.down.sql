@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE contract_call_approved_events
+    DROP COLUMN execute_tx_hash,
+    DROP COLUMN retry_count;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE contract_call_approved_events
+    ADD COLUMN retry_count     INT  NOT NULL DEFAULT 0,
+    ADD COLUMN execute_tx_hash TEXT;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE contract_call_approved_events
+    ADD COLUMN revert_reason TEXT;
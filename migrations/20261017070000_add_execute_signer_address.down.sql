@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE contract_call_approved_events
+    DROP COLUMN signer_address;
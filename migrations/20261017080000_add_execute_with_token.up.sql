@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE contract_call_approved_events
+    ADD COLUMN symbol TEXT,
+    ADD COLUMN amount NUMERIC;
@@ -0,0 +1,127 @@
+use axum::extract::{Path, State};
+use axum::http::{header, Request, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{error, info};
+
+use crate::api::routes::{ApiError, Execution};
+use crate::api::server::ApiState;
+use crate::carbon::retry::queue_start_relay;
+use crate::db::carbon_events::{get_axelar_call_contract_events_by_nonce, get_pending_action_by_nonce, transition_relay_status};
+use crate::db::evm_events::{get_contract_call_approved_event_by_id, get_contract_call_approved_events_by_payload_hashes, transition_broadcast_status};
+use crate::db::{RelayStatus, StatusChange};
+
+// write endpoints, all requests must carry `Authorization: Bearer <admin_token>`
+pub fn routes(state: ApiState) -> Router<ApiState> {
+    Router::new()
+        .route("/pending-actions/:nonce/requeue", post(requeue_pending_action))
+        .route("/pending-actions/:nonce/abandon", post(abandon_pending_action))
+        .route("/executions/:id/retry", post(retry_execution))
+        .route_layer(middleware::from_fn_with_state(state, require_admin_token))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AbandonParams {
+    pub reason: Option<String>,
+}
+
+async fn require_admin_token<B>(State(state): State<ApiState>, request: Request<B>, next: Next<B>) -> Result<Response, ApiError> {
+    let admin_token = match state.conf.api.as_ref().and_then(|api| api.admin_token.as_deref()) {
+        Some(token) if !token.is_empty() => token,
+        _ => return Err(ApiError::NotFound("admin api is disabled".to_string())),
+    };
+
+    let bearer = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match bearer {
+        Some(token) if constant_time_eq(token.as_bytes(), admin_token.as_bytes()) => Ok(next.run(request).await),
+        _ => Err(ApiError::Unauthorized("invalid or missing admin token".to_string())),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// POST /pending-actions/{nonce}/requeue
+// pushes the nonce back into the carbon broadcaster so that the relay is started again
+async fn requeue_pending_action(State(state): State<ApiState>, Path(nonce): Path<i64>) -> Result<Response, ApiError> {
+    let pending_action = get_pending_action_by_nonce(state.pg_pool.clone(), nonce).await?
+        .ok_or_else(|| ApiError::NotFound(format!("pending action with nonce {} not found", nonce)))?;
+    let status = pending_action.get_status();
+    if !RelayStatus::unstarted().contains(&status) {
+        return Err(ApiError::Conflict(format!("pending action with nonce {} is {} and cannot be requeued", nonce, status)))
+    }
+
+    // the status is moved atomically, a relay that was started in the meantime is not requeued
+    if status != RelayStatus::Queued && !transition_relay_status(state.pg_pool.clone(), nonce, RelayStatus::Queued, &StatusChange::reason("requeued by admin")).await? {
+        return Err(ApiError::Conflict(format!("pending action with nonce {} changed status and cannot be requeued", nonce)))
+    }
+
+    info!("Requeuing start relay for nonce {:?} from admin api", nonce);
+    tokio::spawn(async move {
+        queue_start_relay(&state.conf.carbon, state.pg_pool.clone(), state.carbon_broadcaster.clone(), nonce).await;
+    });
+    Ok((StatusCode::ACCEPTED, Json(json!({ "nonce": nonce, "status": "requeued" }))).into_response())
+}
+
+// POST /executions/{id}/retry
+// resets a failed execution so that the evm broadcaster picks it up again
+async fn retry_execution(State(state): State<ApiState>, Path(id): Path<i32>) -> Result<Json<Execution>, ApiError> {
+    let event = get_contract_call_approved_event_by_id(state.pg_pool.clone(), id).await?
+        .ok_or_else(|| ApiError::NotFound(format!("execution with id {} not found", id)))?;
+    if event.broadcast_status != "failed" {
+        return Err(ApiError::Conflict(format!("execution with id {} is {} and cannot be retried", id, &event.broadcast_status)))
+    }
+
+    info!("Resetting execution {:?} on {:?} to pending_broadcast from admin api", id, &event.blockchain);
+    // reconciliation or another admin call can move the execution in the meantime, it is only reset if still failed
+    if !transition_broadcast_status(state.pg_pool.clone(), &event, "failed", "pending_broadcast", &StatusChange::reason("reset by admin")).await? {
+        return Err(ApiError::Conflict(format!("execution with id {} is no longer failed and cannot be retried", id)))
+    }
+
+    let event = get_contract_call_approved_event_by_id(state.pg_pool.clone(), id).await?
+        .ok_or_else(|| ApiError::NotFound(format!("execution with id {} not found", id)))?;
+    Ok(Json(Execution::from(event)))
+}
+
+// POST /pending-actions/{nonce}/abandon {"reason": "..."}
+// marks the action as abandoned and stops any executions that have not been broadcasted yet
+async fn abandon_pending_action(State(state): State<ApiState>, Path(nonce): Path<i64>, params: Option<Json<AbandonParams>>) -> Result<Response, ApiError> {
+    let pending_action = get_pending_action_by_nonce(state.pg_pool.clone(), nonce).await?
+        .ok_or_else(|| ApiError::NotFound(format!("pending action with nonce {} not found", nonce)))?;
+    let status = pending_action.get_status();
+    if status.is_terminal() {
+        return Err(ApiError::Conflict(format!("pending action with nonce {} is already {}", nonce, status)))
+    }
+
+    let reason = params.and_then(|Json(params)| params.reason).unwrap_or_else(|| "abandoned by admin".to_string());
+    let change = StatusChange::reason(reason);
+    info!("Abandoning nonce {:?} from admin api, reason: {:?}", nonce, &change.reason);
+    if !transition_relay_status(state.pg_pool.clone(), nonce, RelayStatus::Abandoned, &change).await? {
+        return Err(ApiError::Conflict(format!("pending action with nonce {} could not be abandoned", nonce)))
+    }
+
+    let axelar_call_contract_events = get_axelar_call_contract_events_by_nonce(state.pg_pool.clone(), nonce).await?;
+    let payload_hashes: Vec<String> = axelar_call_contract_events.iter().map(|e| e.payload_hash.clone()).collect();
+    let executions = get_contract_call_approved_events_by_payload_hashes(state.pg_pool.clone(), &payload_hashes).await?;
+    for event in executions.iter().filter(|e| e.broadcast_status == "pending_broadcast" || e.broadcast_status == "failed") {
+        // an execution picked up by the broadcaster in the meantime is left to finish
+        match transition_broadcast_status(state.pg_pool.clone(), event, &event.broadcast_status, "abandoned", &change).await {
+            Ok(true) => {}
+            Ok(false) => info!("Not abandoning execution {:?} as it is no longer {}", event.id, &event.broadcast_status),
+            Err(e) => error!("Failed to abandon execution {:?}, err: {:?}", event.id, e),
+        }
+    }
+
+    Ok((StatusCode::OK, Json(json!({ "nonce": nonce, "status": RelayStatus::Abandoned.as_str() }))).into_response())
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::error;
+
+use crate::api::admin;
+use crate::api::server::ApiState;
+use crate::db::audit_log::get_relay_audit_logs_by_nonce;
+use crate::db::carbon_events::{get_axelar_call_contract_events_by_nonce, get_pending_action_by_nonce, get_pending_actions};
+use crate::db::checkpoints::get_chain_checkpoints;
+use crate::db::evm_events::{count_contract_call_approved_events, get_contract_call_approved_events, get_contract_call_approved_events_by_payload_hashes};
+use crate::db::{DbAxelarCallContractEvent, DbContractCallApprovedEvent, DbPendingActionEvent, DbRelayAuditLog, RelayStatus};
+use crate::health;
+use crate::metrics;
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/pending-actions", get(list_pending_actions))
+        .route("/pending-actions/:nonce", get(get_pending_action))
+        .route("/executions", get(list_executions))
+        .route("/chains", get(list_chains))
+        .route("/metrics", get(get_metrics))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .merge(admin::routes(state.clone()))
+        .with_state(state)
+}
+
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    NotFound(String),
+    Conflict(String),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Conflict(message) => (StatusCode::CONFLICT, message),
+            ApiError::Internal(e) => {
+                error!("API request failed: {:?}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal error".to_string())
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+// contract_call_approved_events as returned by the api
+#[derive(Debug, Clone, Serialize)]
+pub struct Execution {
+    pub id: i32,
+    pub blockchain: String,
+    pub broadcast_status: String,
+    pub command_id: String,
+    pub source_chain: String,
+    pub source_address: String,
+    pub contract_address: String,
+    pub payload_hash: String,
+    pub source_tx_hash: String,
+    pub source_event_index: String,
+    pub payload: String,
+    pub retry_count: i32,
+    pub execute_tx_hash: Option<String>,
+    pub gas_estimate: Option<i64>,
+    pub revert_reason: Option<String>,
+    pub signer_address: Option<String>,
+    pub symbol: Option<String>,
+    pub amount: Option<String>,
+}
+
+impl From<DbContractCallApprovedEvent> for Execution {
+    fn from(event: DbContractCallApprovedEvent) -> Self {
+        Execution {
+            id: event.id,
+            blockchain: event.blockchain,
+            broadcast_status: event.broadcast_status,
+            command_id: event.command_id,
+            source_chain: event.source_chain,
+            source_address: event.source_address,
+            contract_address: event.contract_address,
+            payload_hash: event.payload_hash,
+            source_tx_hash: event.source_tx_hash,
+            source_event_index: event.source_event_index.to_string(),
+            payload: event.payload,
+            retry_count: event.retry_count,
+            execute_tx_hash: event.execute_tx_hash,
+            gas_estimate: event.gas_estimate,
+            revert_reason: event.revert_reason,
+            signer_address: event.signer_address,
+            symbol: event.symbol,
+            amount: event.amount.map(|amount| amount.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingActionDetails {
+    pub pending_action: DbPendingActionEvent,
+    pub axelar_call_contract_events: Vec<DbAxelarCallContractEvent>,
+    pub executions: Vec<Execution>,
+    pub audit_log: Vec<DbRelayAuditLog>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChainStatus {
+    pub chain_id: String,
+    pub last_processed_block: Option<i64>,
+    pub updated_at: Option<DateTime<Utc>>,
+    // number of executions by broadcast_status, empty for carbon
+    pub executions: HashMap<String, i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingActionsParams {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExecutionsParams {
+    pub status: Option<String>,
+    pub chain: Option<String>,
+    pub limit: Option<i64>,
+}
+
+fn parse_limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+// GET /pending-actions?status=queued&limit=100
+async fn list_pending_actions(State(state): State<ApiState>, Query(params): Query<PendingActionsParams>) -> Result<Json<Vec<DbPendingActionEvent>>, ApiError> {
+    let status = match params.status {
+        Some(status) => Some(RelayStatus::from_str(&status).map_err(|e| ApiError::BadRequest(e.to_string()))?),
+        None => None,
+    };
+    let pending_actions = get_pending_actions(state.pg_pool.clone(), status, parse_limit(params.limit)).await?;
+    Ok(Json(pending_actions))
+}
+
+// GET /pending-actions/{nonce}
+async fn get_pending_action(State(state): State<ApiState>, Path(nonce): Path<i64>) -> Result<Json<PendingActionDetails>, ApiError> {
+    let pending_action = get_pending_action_by_nonce(state.pg_pool.clone(), nonce).await?
+        .ok_or_else(|| ApiError::NotFound(format!("pending action with nonce {} not found", nonce)))?;
+    let axelar_call_contract_events = get_axelar_call_contract_events_by_nonce(state.pg_pool.clone(), nonce).await?;
+    let payload_hashes: Vec<String> = axelar_call_contract_events.iter().map(|e| e.payload_hash.clone()).collect();
+    let executions = get_contract_call_approved_events_by_payload_hashes(state.pg_pool.clone(), &payload_hashes).await?;
+    let audit_log = get_relay_audit_logs_by_nonce(state.pg_pool.clone(), nonce).await?;
+
+    Ok(Json(PendingActionDetails {
+        pending_action,
+        axelar_call_contract_events,
+        executions: executions.into_iter().map(Execution::from).collect(),
+        audit_log,
+    }))
+}
+
+// GET /executions?status=failed&chain=bsc-testnet&limit=100
+async fn list_executions(State(state): State<ApiState>, Query(params): Query<ExecutionsParams>) -> Result<Json<Vec<Execution>>, ApiError> {
+    let executions = get_contract_call_approved_events(state.pg_pool.clone(), params.status.as_deref(), params.chain.as_deref(), parse_limit(params.limit)).await?;
+    Ok(Json(executions.into_iter().map(Execution::from).collect()))
+}
+
+// GET /chains
+async fn list_chains(State(state): State<ApiState>) -> Result<Json<Vec<ChainStatus>>, ApiError> {
+    let checkpoints = get_chain_checkpoints(state.pg_pool.clone()).await?;
+    let counts = count_contract_call_approved_events(state.pg_pool.clone()).await?;
+
+    let chain_ids = std::iter::once(state.conf.carbon.chain_id.clone())
+        .chain(state.conf.evm_chains.iter().map(|chain| chain.chain_id.clone()));
+    let chains = chain_ids
+        .map(|chain_id| {
+            let checkpoint = checkpoints.iter().find(|c| c.chain_id == chain_id);
+            let executions = counts.iter()
+                .filter(|(blockchain, _, _)| blockchain == &chain_id)
+                .map(|(_, broadcast_status, count)| (broadcast_status.clone(), *count))
+                .collect();
+            ChainStatus {
+                last_processed_block: checkpoint.map(|c| c.last_processed_block),
+                updated_at: checkpoint.map(|c| c.updated_at),
+                chain_id,
+                executions,
+            }
+        })
+        .collect();
+    Ok(Json(chains))
+}
+
+// GET /metrics in the prometheus text format
+async fn get_metrics() -> Result<String, ApiError> {
+    Ok(metrics::gather()?)
+}
+
+// GET /healthz fails once any long-running task has exited, so that the process gets restarted
+async fn healthz() -> Response {
+    let healthy = health::is_live();
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(json!({ "healthy": healthy }))).into_response()
+}
+
+// GET /readyz requires postgres to be reachable and every registered component to be ready
+async fn readyz(State(state): State<ApiState>) -> Response {
+    let postgres = sqlx::query("SELECT 1").execute(state.pg_pool.as_ref()).await.is_ok();
+    let components = health::status();
+    let ready = postgres && components.values().all(|c| c.ready);
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(json!({ "ready": ready, "postgres": postgres, "components": components }))).into_response()
+}
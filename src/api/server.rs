@@ -0,0 +1,31 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use tokio::sync::mpsc::Sender;
+use tracing::{info, instrument};
+
+use crate::api::routes;
+use crate::carbon::broadcaster::BroadcastRequest;
+use crate::conf::{Api, AppConfig};
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub conf: AppConfig,
+    pub pg_pool: Arc<PgPool>,
+    pub carbon_broadcaster: Sender<BroadcastRequest>,
+}
+
+#[instrument(name = "api", skip_all)]
+pub async fn init(api_config: &Api, state: ApiState) -> Result<()> {
+    let addr = api_config.bind_address.parse::<SocketAddr>()
+        .with_context(|| format!("Invalid api bind_address {:?}", &api_config.bind_address))?;
+    info!("Starting API server on {:?}", addr);
+
+    axum::Server::try_bind(&addr)
+        .with_context(|| format!("Failed to bind API server to {:?}", addr))?
+        .serve(routes::router(state).into_make_service())
+        .await
+        .context("API server failed")
+}
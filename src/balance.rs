@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use ethers::prelude::*;
+use reqwest::Client;
+use tokio::time::interval;
+use tracing::{error, info, instrument, warn};
+
+use crate::conf::{AppConfig, Carbon, Chain};
+use crate::evm::gas::GasPricing;
+use crate::health;
+use crate::signer::evm::load_signers;
+use crate::metrics::{EXECUTOR_PAUSED, WALLET_BALANCE};
+
+const BALANCE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+// evm chains where no relayer account can pay for an execute, relays to them are not started
+static PAUSED_CHAINS: LazyLock<RwLock<HashSet<String>>> = LazyLock::new(|| RwLock::new(HashSet::new()));
+
+// whether a relay to the chain can be started, chains are assumed funded until their balances are first checked
+pub fn can_pay(chain_id: &str) -> bool {
+    !PAUSED_CHAINS.read().unwrap().contains(chain_id)
+}
+
+// periodically checks the balances of the relayer accounts on carbon and every evm chain
+#[instrument(name = "balance", skip_all)]
+pub async fn init_all(conf: AppConfig) -> Result<()> {
+    let _guard = health::task_started("balance");
+    // addresses are derived once as keystores are slow to decrypt and remote signers need a round trip,
+    // failing to load them fails the task so that the supervisor retries
+    let mut evm_chains = Vec::new();
+    for chain in conf.evm_chains.clone() {
+        let signers = load_signers(&chain).await
+            .with_context(|| format!("Failed to load relayer keys of {}", &chain.chain_id))?;
+        let addresses = signers.iter().map(|signer| signer.address()).collect::<Vec<_>>();
+        evm_chains.push((chain, addresses));
+    }
+
+    let mut interval = interval(BALANCE_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = check_carbon_balance(&conf.carbon).await {
+            error!("Failed to check carbon relayer balance: {:?}", e);
+        }
+        for (chain, addresses) in &evm_chains {
+            if let Err(e) = check_evm_balances(chain, addresses).await {
+                error!("Failed to check relayer balances on {}: {:?}", &chain.chain_id, e);
+            }
+        }
+    }
+}
+
+async fn check_carbon_balance(carbon: &Carbon) -> Result<()> {
+    let denom = carbon.fee_denom.clone().unwrap_or_else(|| "swth".to_string());
+    let balance = get_bank_balance(&carbon.rest_url, &carbon.relayer_address, &denom).await?;
+    WALLET_BALANCE.with_label_values(&[&carbon.chain_id, &carbon.relayer_address]).set(balance as f64);
+    if let Some(threshold) = carbon.low_balance_threshold {
+        if balance < threshold as u128 {
+            warn!("Carbon relayer {} has {}{}, below the threshold of {}{}", &carbon.relayer_address, balance, &denom, threshold, &denom);
+        }
+    }
+    Ok(())
+}
+
+// the relays to a chain are paused when none of its relayer accounts can pay for an execute at the current gas price,
+// priced the way the broadcaster does including its replacements
+async fn check_evm_balances(chain: &Chain, addresses: &[Address]) -> Result<()> {
+    let provider = Provider::<Http>::try_from(chain.rpc_url.as_str())?;
+    let market_pricing = GasPricing::fetch(&provider, chain.tx_type).await?;
+    let execute_cost = chain.gas_policy.max_execute_cost(market_pricing, chain.gas_limit)?;
+
+    let mut can_pay = false;
+    for &address in addresses {
+        let balance = provider.get_balance(address, None).await.context("Failed to get balance")?;
+        let balance_ether = ethers::utils::format_ether(balance).parse::<f64>().unwrap_or_default();
+        WALLET_BALANCE.with_label_values(&[&chain.chain_id, &format!("{:?}", address)]).set(balance_ether);
+        if let Some(threshold) = chain.low_balance_threshold {
+            if balance_ether < threshold {
+                warn!("Relayer {:?} on {} has {}, below the threshold of {}", address, &chain.chain_id, balance_ether, threshold);
+            }
+        }
+        can_pay |= balance >= execute_cost;
+    }
+
+    let mut paused_chains = PAUSED_CHAINS.write().unwrap();
+    if !can_pay && paused_chains.insert(chain.chain_id.clone()) {
+        warn!("Pausing relays to {} as no relayer account can pay {} wei for an execute", &chain.chain_id, execute_cost);
+    } else if can_pay && paused_chains.remove(&chain.chain_id) {
+        info!("Resuming relays to {}", &chain.chain_id);
+    }
+    EXECUTOR_PAUSED.with_label_values(&[&chain.chain_id]).set(!can_pay as i64);
+    Ok(())
+}
+
+// balance of a denom from the bank module's REST endpoint
+async fn get_bank_balance(rest_url: &str, address: &str, denom: &str) -> Result<u128> {
+    let client = Client::new();
+    let url = format!("{}/cosmos/bank/v1beta1/balances/{}/by_denom?denom={}", rest_url, address, denom);
+    let resp: serde_json::Value = client.get(&url).send().await?.json().await?;
+    let amount = resp["balance"]["amount"]
+        .as_str()
+        .ok_or_else(|| anyhow!("balance not found"))?
+        .parse::<u128>()?;
+    Ok(amount)
+}
@@ -1,15 +1,20 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use prost_types::Any;
 use tokio::sync::{mpsc, oneshot};
 use tokio::sync::mpsc::Sender;
-use tracing::instrument;
+use tracing::{info, instrument, warn};
 
 use crate::conf::Carbon;
+use crate::health;
+use crate::shutdown;
 use crate::util::carbon::msg::IntoAny;
-use crate::util::carbon::tx::send_msg_via_tx;
+use crate::util::carbon::tx::send_msgs_via_tx;
+use crate::util::cosmos::{DeliveredTx, TxNotIncluded};
 
 pub struct BroadcastRequest {
     pub msg: Box<dyn IntoAny>,
-    pub callback: oneshot::Sender<Result<serde_json::Value>>,
+    // the included tx, which can have failed in DeliverTx, or an error if it could not be broadcasted or included in time
+    pub callback: oneshot::Sender<Result<DeliveredTx>>,
 }
 
 #[instrument(name = "broadcaster_carbon", skip_all)]
@@ -24,16 +29,71 @@ pub async fn init_all(carbon_config: &Carbon) -> Sender<BroadcastRequest> {
 pub async fn init_channel(carbon_config: &Carbon) -> Sender<BroadcastRequest> {
     let (broadcast_tx, mut broadcast_rx) = mpsc::channel::<BroadcastRequest>(100);
     let carbon_config = carbon_config.clone();
+    let max_msgs_per_tx = carbon_config.max_msgs_per_tx.unwrap_or(20).max(1);
     tokio::spawn(async move {
+        let _guard = health::task_started("carbon_broadcaster");
+        // keeps running on shutdown until the process exits, so that requests queued by producers that are being
+        // drained are still sent. the producers hold an in-flight guard until their callback is answered
         while let Some(request) = broadcast_rx.recv().await {
-            // Simulate broadcasting msg
-            println!("Broadcasting: {:?}", request.msg);
+            let _in_flight = shutdown::in_flight();
 
-            let response = send_msg_via_tx(&carbon_config, request.msg.into_any()).await;
-
-            // Send the response via the callback channel
-            let _ = request.callback.send(response);
+            // requests queued up while the previous tx was sent go out together in one tx
+            let mut batch = vec![request];
+            while batch.len() < max_msgs_per_tx {
+                match broadcast_rx.try_recv() {
+                    Ok(request) => batch.push(request),
+                    Err(_) => break,
+                }
+            }
+            broadcast_batch(&carbon_config, batch).await;
         }
     });
     broadcast_tx
-}
\ No newline at end of file
+}
+
+// every request gets the response of the tx its msg was sent in
+async fn broadcast_batch(carbon_config: &Carbon, batch: Vec<BroadcastRequest>) {
+    let (msgs, callbacks): (Vec<Any>, Vec<_>) = batch.into_iter()
+        .map(|request| {
+            info!("Broadcasting: {:?}", request.msg);
+            (request.msg.into_any(), request.callback)
+        })
+        .unzip();
+
+    if msgs.len() > 1 {
+        info!("Broadcasting {} msgs in one tx", msgs.len());
+    }
+    let response = send_msgs_via_tx(carbon_config, msgs.clone()).await;
+    // msgs are only sent again when nothing was broadcasted or the tx failed in DeliverTx.
+    // a tx that was not included in time can still be included, so its msgs are not sent again
+    let failed = match &response {
+        Ok(tx) => !tx.is_success(),
+        Err(e) => e.downcast_ref::<TxNotIncluded>().is_none(),
+    };
+    if failed && msgs.len() > 1 {
+        // a single bad msg fails the whole tx, so the msgs are sent one by one to find out which
+        warn!("Failed to broadcast {} msgs in one tx, sending them separately: {:?}", msgs.len(), response);
+        for (msg, callback) in msgs.into_iter().zip(callbacks) {
+            let response = send_msgs_via_tx(carbon_config, vec![msg]).await;
+            let _ = callback.send(response);
+        }
+        return
+    }
+    match response {
+        Ok(tx) => {
+            for callback in callbacks {
+                let _ = callback.send(Ok(tx.clone()));
+            }
+        }
+        Err(e) => {
+            // every msg of the batch shares the error, a TxNotIncluded is kept as is so that it can still be told apart
+            for callback in callbacks {
+                let e = match e.downcast_ref::<TxNotIncluded>() {
+                    Some(e) => e.clone().into(),
+                    None => anyhow!("{:#}", e),
+                };
+                let _ = callback.send(Err(e));
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use futures::lock::Mutex;
 use num_traits::ToPrimitive;
@@ -11,9 +12,11 @@ use crate::carbon::broadcaster::BroadcastRequest;
 use crate::carbon::retry::{is_whitelisted_or_sufficient_fees, queue_start_relay};
 use crate::conf::{Carbon, Fee};
 use crate::constants::events::{CARBON_AXELAR_CALL_CONTRACT_EVENT, CARBON_BRIDGE_EXPIRED_PENDING_ACTION_EVENT, CARBON_BRIDGE_PENDING_ACTION_EVENT, CARBON_BRIDGE_REVERT_EVENT, CARBON_UPDATE_PENDING_ACTION_EVENT};
-use crate::db::carbon_events::{delete_bridge_pending_action_event, save_axelar_call_contract_event, save_bridge_pending_action_event, update_bridge_pending_action_event};
+use crate::db::checkpoints::save_chain_checkpoint;
+use crate::db::carbon_events::{save_axelar_call_contract_event, save_bridge_pending_action_event, transition_relay_status, update_bridge_pending_action_event};
+use crate::db::{RelayStatus, StatusChange};
 use crate::util::carbon::parser::{parse_axelar_call_contract_event, parse_bridge_pending_action_event, parse_bridge_reverted_event, parse_expired_pending_action_event};
-use crate::util::cosmos::extract_events;
+use crate::util::cosmos::{extract_block_height, extract_events, extract_tx_info};
 use crate::ws::JSONWebSocketClient;
 
 #[instrument(name = "listener_carbon", skip_all)]
@@ -21,8 +24,9 @@ pub async fn init_ws(carbon_config: &Carbon, fee_config: &Fee, pg_pool: Arc<PgPo
     info!("Initializing WS for Carbon. Watching {:?} on {:?} for events", &carbon_config.relayer_address, &carbon_config.ws_url);
     let url = Url::parse(&carbon_config.ws_url).expect(&format!("Invalid WS URL {:?}", &carbon_config.ws_url));
 
-    // create new client
-    let mut client = JSONWebSocketClient::new(url);
+    // create new client, a new block header is expected every few seconds while connected
+    let mut client = JSONWebSocketClient::new(url)
+        .with_health_check("carbon_ws", Duration::from_secs(60));
 
     // add WithdrawTokenConfirmedEvent subscription
     let pool = pg_pool.clone();
@@ -97,6 +101,21 @@ pub async fn init_ws(carbon_config: &Carbon, fee_config: &Fee, pg_pool: Arc<PgPo
             });
         })));
 
+    // add NewBlockHeader subscription to keep track of the last processed block
+    let pool = pg_pool.clone();
+    let chain_id = carbon_config.chain_id.clone();
+    client.add_cosmos_subscription(
+        "6".to_string(),
+        "tm.event='NewBlockHeader'",
+        Arc::new(Mutex::new(move |msg: String| {
+            let pool = pool.clone();
+            let chain_id = chain_id.clone();
+            // Spawn an async task to handle the message
+            tokio::spawn(async move {
+                process_new_block_header(msg, &chain_id, pool.clone()).await;
+            });
+        })));
+
     // connect to WS
     if let Err(e) = client.connect().await {
         error!("Error connecting to client: {:?}", e);
@@ -110,18 +129,23 @@ pub async fn init_ws(carbon_config: &Carbon, fee_config: &Fee, pg_pool: Arc<PgPo
 async fn process_bridge_pending_action(carbon_config: &Carbon, fee_config: &Fee, msg: String, pg_pool: Arc<PgPool>, carbon_broadcaster: Sender<BroadcastRequest>) {
     info!("Processing new PendingActionEvent from Carbon");
     let events = extract_events(&msg, CARBON_BRIDGE_PENDING_ACTION_EVENT).unwrap();
+    let (height, tx_hash) = extract_tx_info(&msg).unwrap_or_default();
     for event in events {
         let pending_action = parse_bridge_pending_action_event(event);
 
-        // check if event has expired
+        // save to DB
+        save_bridge_pending_action_event(pg_pool.clone(), &pending_action.clone()).await;
+
+        // check if event has expired, it is kept so that we know it was seen
         if pending_action.get_relay_details().has_expired() {
             info!("Skipping event with nonce {:?} as it has expired", pending_action.nonce.to_u64());
+            let change = StatusChange::reason("expired before it was observed").with_carbon_tx(height, tx_hash.clone());
+            if let Err(e) = transition_relay_status(pg_pool.clone(), pending_action.nonce, RelayStatus::Expired, &change).await {
+                error!("Failed to transition relay status for nonce {:?}, err: {:?}", pending_action.nonce, e);
+            }
             continue
         }
 
-        // save to DB
-        save_bridge_pending_action_event(pg_pool.clone(), &pending_action.clone()).await;
-
         // start the relay
         let pg_pool = pg_pool.clone();
         let carbon_config = carbon_config.clone();
@@ -131,6 +155,8 @@ async fn process_bridge_pending_action(carbon_config: &Carbon, fee_config: &Fee,
             let _ = tokio::spawn(async move {
                 queue_start_relay(&carbon_config.clone(), pg_pool.clone(), carbon_broadcaster.clone(), pending_action.nonce).await;
             });
+        } else if let Err(e) = transition_relay_status(pg_pool.clone(), pending_action.nonce, RelayStatus::FeeRejected, &StatusChange::reason("not whitelisted and insufficient fees").with_carbon_tx(height, tx_hash.clone())).await {
+            error!("Failed to transition relay status for nonce {:?}, err: {:?}", pending_action.nonce, e);
         }
     }
 }
@@ -140,11 +166,19 @@ async fn process_bridge_pending_action(carbon_config: &Carbon, fee_config: &Fee,
 async fn process_bridge_update_pending_action(msg: String, pg_pool: Arc<PgPool>) {
     info!("Processing new PendingActionEvent from Carbon");
     let events = extract_events(&msg, CARBON_UPDATE_PENDING_ACTION_EVENT).unwrap();
+    let (height, tx_hash) = extract_tx_info(&msg).unwrap_or_default();
     for event in events {
         let pending_action = parse_bridge_pending_action_event(event);
 
         // update in DB
         update_bridge_pending_action_event(pg_pool.clone(), &pending_action.clone()).await;
+
+        // sent_at is only set once the relay has been started, by us or by another relayer
+        if pending_action.get_relay_details().is_sent() {
+            if let Err(e) = transition_relay_status(pg_pool.clone(), pending_action.nonce, RelayStatus::RelayStarted, &StatusChange::reason("relay started on carbon").with_carbon_tx(height, tx_hash.clone())).await {
+                error!("Failed to transition relay status for nonce {:?}, err: {:?}", pending_action.nonce, e);
+            }
+        }
     }
 }
 
@@ -153,9 +187,12 @@ async fn process_bridge_update_pending_action(msg: String, pg_pool: Arc<PgPool>)
 async fn process_expired_pending_action_event(msg: String, pg_pool: Arc<PgPool>) {
     info!("Processing new BridgeRevertedEvent from Carbon");
     let events = extract_events(&msg, CARBON_BRIDGE_EXPIRED_PENDING_ACTION_EVENT).unwrap();
+    let (height, tx_hash) = extract_tx_info(&msg).unwrap_or_default();
     for event in events {
         let expired_pending_action_event = parse_expired_pending_action_event(event);
-        delete_bridge_pending_action_event(pg_pool.clone(), expired_pending_action_event.nonce).await
+        if let Err(e) = transition_relay_status(pg_pool.clone(), expired_pending_action_event.nonce, RelayStatus::Expired, &StatusChange::reason("pending action expired on carbon").with_carbon_tx(height, tx_hash.clone())).await {
+            error!("Failed to transition relay status for nonce {:?}, err: {:?}", expired_pending_action_event.nonce, e);
+        }
     }
 }
 
@@ -164,9 +201,12 @@ async fn process_expired_pending_action_event(msg: String, pg_pool: Arc<PgPool>)
 async fn process_bridge_reverted_event(msg: String, pg_pool: Arc<PgPool>) {
     info!("Processing new BridgeRevertedEvent from Carbon");
     let events = extract_events(&msg, CARBON_BRIDGE_REVERT_EVENT).unwrap();
+    let (height, tx_hash) = extract_tx_info(&msg).unwrap_or_default();
     for event in events {
         let bridge_reverted_event = parse_bridge_reverted_event(event);
-        delete_bridge_pending_action_event(pg_pool.clone(), bridge_reverted_event.nonce).await
+        if let Err(e) = transition_relay_status(pg_pool.clone(), bridge_reverted_event.nonce, RelayStatus::Reverted, &StatusChange::reason("bridge reverted on carbon").with_carbon_tx(height, tx_hash.clone())).await {
+            error!("Failed to transition relay status for nonce {:?}, err: {:?}", bridge_reverted_event.nonce, e);
+        }
     }
 }
 
@@ -175,8 +215,26 @@ async fn process_bridge_reverted_event(msg: String, pg_pool: Arc<PgPool>) {
 async fn process_axelar_call_contract_event(msg: String, pg_pool: Arc<PgPool>) {
     info!("Processing new AxelarCallContractEvent from Carbon");
     let events = extract_events(&msg, CARBON_AXELAR_CALL_CONTRACT_EVENT).unwrap();
+    let (height, tx_hash) = extract_tx_info(&msg).unwrap_or_default();
     for event in events {
         let axelar_call_contract_event = parse_axelar_call_contract_event(event);
-        save_axelar_call_contract_event(pg_pool.clone(), &axelar_call_contract_event.clone()).await
+        save_axelar_call_contract_event(pg_pool.clone(), &axelar_call_contract_event.clone()).await;
+        if let Err(e) = transition_relay_status(pg_pool.clone(), axelar_call_contract_event.nonce, RelayStatus::CallContractSeen, &StatusChange::reason("axelar call contract emitted on carbon").with_carbon_tx(height, tx_hash.clone())).await {
+            error!("Failed to transition relay status for nonce {:?}, err: {:?}", axelar_call_contract_event.nonce, e);
+        }
+    }
+}
+
+// process_new_block_header saves the height of the NewBlockHeader as the last processed block
+async fn process_new_block_header(msg: String, chain_id: &str, pg_pool: Arc<PgPool>) {
+    let height = match extract_block_height(&msg) {
+        Ok(height) => height,
+        Err(e) => {
+            error!("Failed to extract height from NewBlockHeader: {:?}", e);
+            return
+        }
+    };
+    if let Err(e) = save_chain_checkpoint(pg_pool, chain_id, height).await {
+        error!("Failed to save checkpoint for {} at height {}, err: {:?}", chain_id, height, e);
     }
 }
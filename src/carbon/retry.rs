@@ -2,19 +2,24 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
+use futures::future::join_all;
 use num_traits::ToPrimitive;
 use sqlx::PgPool;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::oneshot;
 use tokio::time::interval;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 use tracing::log::debug;
 
+use crate::balance;
 use crate::carbon::broadcaster::BroadcastRequest;
 use crate::conf::{Carbon, Fee};
-use crate::db::carbon_events::{add_bridge_pending_action_event_retry_count, delete_bridge_pending_action_events, get_expired_pending_action_events, save_bridge_pending_action_event};
-use crate::db::{DbPendingActionEvent, PendingActionType};
+use crate::db::carbon_events::{add_bridge_pending_action_event_retry_count, expire_bridge_pending_action_events, get_chain_id_for_nonce, get_expired_pending_action_events, save_bridge_pending_action_event, transition_relay_status};
+use crate::db::{DbPendingActionEvent, PendingActionType, RelayStatus, StatusChange};
 use crate::fee::fee::has_enough_fees;
+use crate::health;
+use crate::shutdown;
+use crate::metrics::START_RELAY_BROADCASTS;
 use crate::util::carbon::msg::{MsgPruneExpiredPendingActions, MsgStartRelay};
 use crate::util::carbon::query::{get_pending_action, get_pending_action_nonces, get_pending_action_relay_details};
 
@@ -27,11 +32,18 @@ pub async fn init_all(carbon_config: &Carbon, fee_config: &Fee, pg_pool: Arc<PgP
 #[instrument(name = "poll_for_pending_action_events", skip_all)]
 async fn poll_for_pending_action_events(carbon_config: &Carbon, fee_config: &Fee, pool: Arc<PgPool>, carbon_broadcaster: Sender<BroadcastRequest>) {
     info!("Watching for events to broadcast");
+    let _guard = health::task_started("carbon_retry");
     let mut interval = interval(Duration::from_secs(60));
     let carbon_config = carbon_config.clone();
     let fee_config = fee_config.clone();
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown::requested() => {
+                info!("Shutting down, no longer polling for pending actions");
+                return
+            }
+        }
         if let Err(e) = backfill_pending_actions(&carbon_config, pool.clone()).await {
             error!("Failed to backfill_pending_actions: {}", e);
         }
@@ -47,14 +59,14 @@ async fn poll_for_pending_action_events(carbon_config: &Carbon, fee_config: &Fee
 // Backfills any pending action found on carbon
 
 async fn backfill_pending_actions(carbon_config: &Carbon, pool: Arc<PgPool>) -> Result<()> {
-    let pending_nonces = get_pending_action_nonces(&carbon_config.rest_url)
+    let pending_nonces = get_pending_action_nonces(carbon_config)
         .await
         .context("Failed to get pending nonces")?;
 
     debug!("Backfilling pending actions: {:?}", pending_nonces);
 
     for nonce in pending_nonces {
-        match get_pending_action(&carbon_config.rest_url, nonce).await {
+        match get_pending_action(carbon_config, nonce).await {
             Ok(pending_action) => {
                 debug!("Fetched pending action: {:?}", pending_action);
                 save_bridge_pending_action_event(pool.clone(), &pending_action).await;
@@ -72,11 +84,11 @@ async fn backfill_pending_actions(carbon_config: &Carbon, pool: Arc<PgPool>) ->
 async fn retry_pending_actions(carbon_config: &Carbon, fee_config: &Fee, pool: Arc<PgPool>, carbon_broadcaster: Sender<BroadcastRequest>) -> Result<()> {
     // check for new events that are not expired
     debug!("Checking for pending_action_events to broadcast...");
-    let events: Vec<DbPendingActionEvent> = sqlx::query_as!(
-        DbPendingActionEvent,
-        "SELECT * FROM pending_action_events WHERE retry_count < $1 AND (relay_details ->> 'expiry_block_time')::timestamp > NOW() AND ((relay_details ->> 'sent_at') IS NULL OR (relay_details ->> 'sent_at') = '')",
-        carbon_config.maximum_start_relay_retry_count
+    let events: Vec<DbPendingActionEvent> = sqlx::query_as::<_, DbPendingActionEvent>(
+        "SELECT * FROM pending_action_events WHERE retry_count < $1 AND (relay_details ->> 'expiry_block_time')::timestamp > NOW() AND ((relay_details ->> 'sent_at') IS NULL OR (relay_details ->> 'sent_at') = '') AND status = ANY($2)",
     )
+        .bind(carbon_config.maximum_start_relay_retry_count)
+        .bind(RelayStatus::unstarted().iter().map(|s| s.to_string()).collect::<Vec<String>>())
         .fetch_all(&*pool)
         .await?;
 
@@ -85,13 +97,18 @@ async fn retry_pending_actions(carbon_config: &Carbon, fee_config: &Fee, pool: A
        return Ok(())
     }
 
+    // every relay is queued before any callback is awaited, so that the broadcaster can batch them into one tx
+    let mut start_relays = Vec::new();
     for pending_action_event in events {
         info!("pending_action_event found in DB: {:?}", pending_action_event);
         let can_relay = is_whitelisted_or_sufficient_fees(fee_config, &pending_action_event).await;
         if can_relay {
-            queue_start_relay(&carbon_config, pool.clone(), carbon_broadcaster.clone(), pending_action_event.nonce).await;
+            start_relays.push(queue_start_relay(carbon_config, pool.clone(), carbon_broadcaster.clone(), pending_action_event.nonce));
+        } else {
+            transition_relay_status(pool.clone(), pending_action_event.nonce, RelayStatus::FeeRejected, &StatusChange::reason("not whitelisted and insufficient fees")).await?;
         }
     }
+    join_all(start_relays).await;
     Ok(())
 }
 
@@ -128,6 +145,23 @@ pub async fn queue_start_relay(carbon_config: &Carbon, pool: Arc<PgPool>, carbon
         return
     }
 
+    // the relay fee would be claimed for an execute that cannot be paid for, it is started once the wallet is funded
+    match get_chain_id_for_nonce(pool.clone(), nonce).await {
+        Ok(Some(chain_id)) if !balance::can_pay(&chain_id) => {
+            warn!("Not starting relay for nonce {:?} as the relayer accounts on {} cannot pay for the execute", nonce, chain_id);
+            return
+        }
+        Ok(_) => {}
+        Err(e) => error!("Failed to get chain id for nonce {:?}, err: {:?}", nonce, e),
+    }
+
+    if let Err(e) = transition_relay_status(pool.clone(), nonce, RelayStatus::Queued, &StatusChange::reason("queued for start relay")).await {
+        error!("Failed to transition relay status for nonce {:?}, err: {:?}", nonce, e);
+    }
+
+    // shutdown waits for the request to be broadcasted and answered
+    let _in_flight = shutdown::in_flight();
+
     // Create a oneshot channel for the response
     let (callback_tx, callback_rx) = oneshot::channel();
 
@@ -144,37 +178,65 @@ pub async fn queue_start_relay(carbon_config: &Carbon, pool: Arc<PgPool>, carbon
     };
 
     // Send the BroadcastRequest through the carbon_broadcaster channel
+    START_RELAY_BROADCASTS.with_label_values(&["attempted"]).inc();
     if let Err(e) = carbon_broadcaster.send(broadcast_request).await {
         eprintln!("Failed to send broadcast request: {:?}", e);
+        START_RELAY_BROADCASTS.with_label_values(&["failed"]).inc();
         return;
     }
 
     // Await the response
     match callback_rx.await {
-        Ok(response) => {
-            match response {
-                Ok(value) => {
-                    info!("Received successful response: {:?}", value);
-                }
-                Err(e) => {
-                    eprintln!("Failed to broadcast message: {:?}", e);
+        Ok(Ok(tx)) if tx.is_success() => {
+            info!("Started relay for nonce {:?} in tx {} at height {}", nonce, &tx.tx_hash, tx.height);
+            START_RELAY_BROADCASTS.with_label_values(&["succeeded"]).inc();
+            let change = StatusChange::reason("relay started on carbon").with_carbon_tx(Some(tx.height), Some(tx.tx_hash.clone()));
+            if let Err(e) = transition_relay_status(pool.clone(), nonce, RelayStatus::RelayStarted, &change).await {
+                error!("Failed to transition relay status for nonce {:?}, err: {:?}", nonce, e);
+            }
+        }
+        Ok(Ok(tx)) => {
+            // the relay may have been started by another relayer in the meantime, which is not a failure
+            if is_relay_sent(carbon_config, nonce).await {
+                info!("Relay for nonce {:?} was already started by someone else, tx {} failed with: {}", nonce, &tx.tx_hash, &tx.raw_log);
+                START_RELAY_BROADCASTS.with_label_values(&["already_started"]).inc();
+                if let Err(e) = transition_relay_status(pool.clone(), nonce, RelayStatus::RelayStarted, &StatusChange::reason("relay started by another relayer")).await {
+                    error!("Failed to transition relay status for nonce {:?}, err: {:?}", nonce, e);
                 }
+                return
             }
-            // Update retry count + 1
+            error!("Failed to start relay for nonce {:?}, tx {} failed with code {} ({}): {}", nonce, &tx.tx_hash, tx.code, &tx.codespace, &tx.raw_log);
+            START_RELAY_BROADCASTS.with_label_values(&["failed"]).inc();
+            add_bridge_pending_action_event_retry_count(pool.clone(), nonce).await.expect("failed");
+        }
+        Ok(Err(e)) => {
+            error!("Failed to broadcast start relay for nonce {:?}: {:?}", nonce, e);
+            START_RELAY_BROADCASTS.with_label_values(&["failed"]).inc();
             add_bridge_pending_action_event_retry_count(pool.clone(), nonce).await.expect("failed");
         }
         Err(e) => {
             eprintln!("Failed to receive callback response: {:?}", e);
+            START_RELAY_BROADCASTS.with_label_values(&["failed"]).inc();
             // Update retry count + 1
             add_bridge_pending_action_event_retry_count(pool.clone(), nonce).await.expect("failed");
         }
     }
 }
 
+// Checks carbon if the relay has been started
+async fn is_relay_sent(carbon_config: &Carbon, nonce: i64) -> bool {
+    match get_pending_action_relay_details(carbon_config, nonce).await {
+        Ok(relay_details) => relay_details.is_sent(),
+        Err(err) => {
+            error!("Error checking action on carbon: {:?}", err);
+            false
+        }
+    }
+}
 
 // Checks carbon if we still need to start this relay
 async fn is_expired_or_sent(carbon_config: &Carbon, nonce: i64) -> bool {
-    let relay_details = get_pending_action_relay_details(&carbon_config.rest_url, nonce).await;
+    let relay_details = get_pending_action_relay_details(carbon_config, nonce).await;
     match relay_details {
         Ok(relay_details) => {
             let is_expired = relay_details.has_expired();
@@ -205,7 +267,7 @@ async fn expire_pending_actions(carbon_config: &Carbon, pool: Arc<PgPool>, carbo
     }
 
     // Prune nonces that are no longer in the "pending" group
-    let nonces_to_process = prune_processed_nonces(&carbon_config.rest_url, pool.clone(), &expired_nonces).await?;
+    let nonces_to_process = prune_processed_nonces(carbon_config, pool.clone(), &expired_nonces).await?;
 
     // TODO: prune nonces that are already sent
 
@@ -215,20 +277,20 @@ async fn expire_pending_actions(carbon_config: &Carbon, pool: Arc<PgPool>, carbo
     Ok(())
 }
 
-pub async fn prune_processed_nonces(rest_url: &str, pool: Arc<PgPool>, expired_nonces: &[i64]) -> Result<Vec<i64>> {
+pub async fn prune_processed_nonces(carbon_config: &Carbon, pool: Arc<PgPool>, expired_nonces: &[i64]) -> Result<Vec<i64>> {
     // Fetch pending nonces from the API
-    let pending_nonces = get_pending_action_nonces(rest_url).await.context("Failed to get pending nonces")?;
+    let pending_nonces = get_pending_action_nonces(carbon_config).await.context("Failed to get pending nonces")?;
 
-    // Find out the nonces we can delete
-    let nonces_to_delete: Vec<i64> = expired_nonces
+    // Find out the nonces we can expire
+    let nonces_to_expire: Vec<i64> = expired_nonces
         .iter()
         .cloned()
         .filter(|nonce| !pending_nonces.contains(nonce))
         .collect();
 
-    // Delete nonces that are no longer pending from the DB
-    if !nonces_to_delete.is_empty() {
-        delete_bridge_pending_action_events(pool.clone(), nonces_to_delete).await?;
+    // Expire nonces that are no longer pending in the DB
+    if !nonces_to_expire.is_empty() {
+        expire_bridge_pending_action_events(pool.clone(), nonces_to_expire, &StatusChange::reason("no longer pending on carbon")).await?;
     }
 
     // Return the nonces that are still pending
@@ -245,6 +307,9 @@ pub async fn prune_processed_nonces(rest_url: &str, pool: Arc<PgPool>, expired_n
 pub async fn queue_expire_relay(carbon_config: &Carbon, carbon_broadcaster: Sender<BroadcastRequest>, nonces: Vec<i64>) {
     info!("Expiring relay on {:?} for nonces {:?}", &carbon_config.rpc_url, &nonces);
 
+    // shutdown waits for the request to be broadcasted and answered
+    let _in_flight = shutdown::in_flight();
+
     // Create a oneshot channel for the response
     let (callback_tx, callback_rx) = oneshot::channel();
 
@@ -268,15 +333,14 @@ pub async fn queue_expire_relay(carbon_config: &Carbon, carbon_broadcaster: Send
 
     // Await the response
     match callback_rx.await {
-        Ok(response) => {
-            match response {
-                Ok(value) => {
-                    info!("Received successful response: {:?}", value);
-                }
-                Err(e) => {
-                    eprintln!("Failed to broadcast message: {:?}", e);
-                }
-            }
+        Ok(Ok(tx)) if tx.is_success() => {
+            info!("Expired nonces {:?} in tx {} at height {}", &nonces, &tx.tx_hash, tx.height);
+        }
+        Ok(Ok(tx)) => {
+            error!("Failed to expire nonces {:?}, tx {} failed with code {} ({}): {}", &nonces, &tx.tx_hash, tx.code, &tx.codespace, &tx.raw_log);
+        }
+        Ok(Err(e)) => {
+            eprintln!("Failed to broadcast message: {:?}", e);
         }
         Err(e) => {
             eprintln!("Failed to receive callback response: {:?}", e);
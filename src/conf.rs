@@ -5,6 +5,8 @@ use dotenvy::dotenv;
 use serde::Deserialize;
 use tracing::info;
 
+use crate::evm::gas::TxType;
+
 #[derive(Clone, Debug, Deserialize)]
 #[allow(unused)]
 pub struct AppConfig {
@@ -15,6 +17,10 @@ pub struct AppConfig {
     pub fee: Fee,
     #[serde(rename = "evm_chain")]
     pub evm_chains: Vec<Chain>,
+    #[serde(default)]
+    pub api: Option<Api>, // the api server is only started if configured
+    #[serde(default)]
+    pub supervisor: Supervisor,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -25,12 +31,27 @@ pub struct Carbon {
     pub rpc_url: String,
     pub rest_url: String,
     pub ws_url: String,
+    // accounts, txs and bridge queries go over grpc instead of the rest api when set
+    #[serde(default)]
+    pub grpc_url: Option<String>,
     pub relayer_address: String,
+    #[serde(default)]
     pub relayer_mnemonic: String,
+    // used instead of relayer_mnemonic when set
+    #[serde(default)]
+    pub relayer_mnemonic_keystore: Option<Keystore>,
+    // used instead of a local mnemonic when set, the key must be derived with the cosmos hd path
+    #[serde(default)]
+    pub remote_signer: Option<RemoteKey>,
     pub account_prefix: String,
     pub maximum_start_relay_retry_count: i32,
     #[serde(default)]
     pub fee_denom: Option<String>,   // <— optional, defaults to None
+    pub low_balance_threshold: Option<u64>, // in fee_denom, a warning is logged below it
+    pub max_msgs_per_tx: Option<usize>, // queued msgs sent together in one tx, defaults to 20
+    pub tx_inclusion_timeout_secs: Option<u64>, // how long a tx is waited for to be included, defaults to 60
+    pub gas_price: Option<f64>, // in fee_denom per unit of gas, defaults to 0.1
+    pub gas_adjustment: Option<f64>, // multiplier of the simulated gas, defaults to 1.5
 }
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -40,6 +61,16 @@ pub enum RelayStrategy {
     GreaterThan0,
 }
 
+impl RelayStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelayStrategy::Hydrogen => "hydrogen",
+            RelayStrategy::All => "all",
+            RelayStrategy::GreaterThan0 => "greater_than0",
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[allow(unused)]
 pub struct Fee {
@@ -58,9 +89,145 @@ pub struct Chain {
     pub axelar_gateway_proxy: String,
     pub carbon_axelar_gateway: String,
     pub max_query_blocks: u64,
+    #[serde(default)]
     pub relayer_private_key: String,
+    // additional relayer keys, executes are spread over all keys round-robin
+    #[serde(default)]
+    pub relayer_private_keys: Vec<String>,
+    #[serde(default)]
+    pub relayer_keystores: Vec<Keystore>,
+    // relayer keys held by remote signing services, used after the local keys
+    #[serde(default)]
+    pub remote_signers: Vec<RemoteKey>,
     pub gas_limit: u64,
     pub backfill_poll_frequency: Option<u64>,
+    pub reconcile_poll_frequency: Option<u64>, // defaults to 300 seconds
+    pub maximum_execute_retry_count: Option<i32>, // defaults to 3
+    #[serde(default)]
+    pub tx_type: TxType, // defaults to legacy
+    #[serde(default)]
+    pub gas_policy: GasPolicy,
+    pub max_in_flight_executes: Option<usize>, // per relayer key, defaults to 1
+    pub low_balance_threshold: Option<f64>, // in the native token, a warning is logged below it
+}
+
+// an encrypted web3 secret-storage keystore, the password is read from an environment variable or a file
+#[derive(Clone, Debug, Deserialize)]
+pub struct Keystore {
+    pub path: String,
+    #[serde(default)]
+    pub password_env: Option<String>,
+    #[serde(default)]
+    pub password_file: Option<String>,
+}
+
+// a key held by a remote signing service, the bearer token for the service is read from an environment variable
+#[derive(Clone, Debug, Deserialize)]
+pub struct RemoteKey {
+    pub url: String,
+    pub key_id: String,
+    #[serde(default)]
+    pub auth_token_env: Option<String>,
+}
+
+impl Chain {
+    // relayer_private_key followed by relayer_private_keys, see relayer_keystores for encrypted keys
+    pub fn signer_keys(&self) -> Vec<&str> {
+        std::iter::once(&self.relayer_private_key)
+            .chain(self.relayer_private_keys.iter())
+            .map(|key| key.as_str())
+            .filter(|key| !key.is_empty())
+            .collect()
+    }
+}
+
+// how execute txs are priced and resubmitted on a chain
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct GasPolicy {
+    // number of replacement txs sent when a tx is not mined in time
+    pub max_retries: u32,
+    // fee increase of each replacement, at least 10% is always used as nodes reject smaller bumps
+    pub bump_percent: u64,
+    pub send_timeout_secs: u64,
+    pub receipt_timeout_secs: u64,
+    // wait before sending a replacement
+    pub resubmit_interval_secs: u64,
+    // hard cap on the gas price (max_fee_per_gas for eip1559 txs), txs are never sent above it
+    pub max_gas_price_gwei: f64,
+    // the gas limit is eth_estimateGas times the multiplier, within the bounds below.
+    // the chain's gas_limit is used when estimation fails
+    pub gas_limit_multiplier: f64,
+    pub min_gas_limit: u64,
+    pub max_gas_limit: u64,
+}
+
+impl Default for GasPolicy {
+    fn default() -> Self {
+        GasPolicy {
+            max_retries: 5,
+            bump_percent: 20,
+            send_timeout_secs: 60,
+            receipt_timeout_secs: 60,
+            resubmit_interval_secs: 30,
+            max_gas_price_gwei: 500.0,
+            gas_limit_multiplier: 1.3,
+            min_gas_limit: 100000,
+            max_gas_limit: 3000000,
+        }
+    }
+}
+
+impl GasPolicy {
+    // bounds that would otherwise only fail, or panic, when an execute is priced
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_gas_limit > self.max_gas_limit {
+            return Err(format!("min_gas_limit {} is above max_gas_limit {}", self.min_gas_limit, self.max_gas_limit))
+        }
+        if self.gas_limit_multiplier.is_nan() || self.gas_limit_multiplier <= 0.0 {
+            return Err(format!("gas_limit_multiplier {} must be above 0", self.gas_limit_multiplier))
+        }
+        if self.max_gas_price_gwei.is_nan() || self.max_gas_price_gwei <= 0.0 {
+            return Err(format!("max_gas_price_gwei {} must be above 0", self.max_gas_price_gwei))
+        }
+        self.max_gas_price().map_err(|e| format!("{:#}", e))?;
+        if self.bump_percent == 0 || self.bump_percent > 100 {
+            return Err(format!("bump_percent {} must be between 1 and 100", self.bump_percent))
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Api {
+    pub bind_address: String,
+    #[serde(default)]
+    pub admin_token: Option<String>, // admin endpoints are disabled when not set
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Supervisor {
+    // number of consecutive failures of a task before the relayer exits
+    pub max_restarts: u32,
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    // a task that ran for this long without failing has its failure count reset
+    pub reset_after_secs: u64,
+    // how long to wait for in-flight broadcasts on SIGINT / SIGTERM
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Supervisor {
+            max_restarts: 10,
+            initial_backoff_secs: 1,
+            max_backoff_secs: 300,
+            reset_after_secs: 600,
+            shutdown_timeout_secs: 120,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -81,7 +248,29 @@ impl AppConfig {
             .build()?;
 
         // Deserialize (and thus freeze) the entire configuration
-        c.try_deserialize()
+        let conf: AppConfig = c.try_deserialize()?;
+        conf.validate()?;
+        Ok(conf)
+    }
+
+    // relayer keys are optional one by one, but a missing key would otherwise only show up on the first tx
+    fn validate(&self) -> Result<(), ConfigError> {
+        let carbon_keys = [
+            !self.carbon.relayer_mnemonic.is_empty(),
+            self.carbon.relayer_mnemonic_keystore.is_some(),
+            self.carbon.remote_signer.is_some(),
+        ];
+        if carbon_keys.iter().filter(|set| **set).count() != 1 {
+            return Err(ConfigError::Message("carbon: exactly one of relayer_mnemonic, relayer_mnemonic_keystore or remote_signer must be set".to_string()))
+        }
+        for chain in &self.evm_chains {
+            if chain.signer_keys().is_empty() && chain.relayer_keystores.is_empty() && chain.remote_signers.is_empty() {
+                return Err(ConfigError::Message(format!("evm_chain {}: no relayer key, set relayer_private_key, relayer_private_keys, relayer_keystores or remote_signers", &chain.chain_id)))
+            }
+            chain.gas_policy.validate()
+                .map_err(|e| ConfigError::Message(format!("evm_chain {}: gas_policy: {}", &chain.chain_id, e)))?;
+        }
+        Ok(())
     }
 }
 //
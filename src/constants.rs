@@ -5,4 +5,5 @@ pub mod events {
     pub const CARBON_UPDATE_PENDING_ACTION_EVENT: &str = "Switcheo.carbon.bridge.UpdatePendingActionEvent";
     pub const CARBON_AXELAR_CALL_CONTRACT_EVENT: &str = "Switcheo.carbon.bridge.ModuleAxelarCallContractEvent";
     pub const EVM_CONTRACT_CALL_APPROVED_EVENT: &str = "ContractCallApproved";
+    pub const EVM_CONTRACT_CALL_APPROVED_WITH_MINT_EVENT: &str = "ContractCallApprovedWithMint";
 }
@@ -1,7 +1,8 @@
+use std::fmt;
 use std::str::FromStr;
 use anyhow::anyhow;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{from_value, Value};
 use sqlx::FromRow;
@@ -9,7 +10,9 @@ use sqlx::types::{BigDecimal, Json, JsonValue};
 
 use crate::util::datetime::{time_difference_str, timestamp_to_datetime};
 
+pub mod audit_log;
 pub mod carbon_events;
+pub mod checkpoints;
 pub mod evm_events;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -22,6 +25,24 @@ pub enum PendingActionType {
     PendingExecuteType,
 }
 
+// lifecycle of a pending action, tracked per nonce on pending_action_events.status
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayStatus {
+    Observed,
+    FeeRejected,
+    Queued,
+    RelayStarted,
+    CallContractSeen,
+    ApprovedOnEvm,
+    Executing,
+    Executed,
+    Failed,
+    Expired,
+    Reverted,
+    Abandoned,
+}
+
 // carbon
 #[derive(Debug, Clone, PartialEq, FromRow, Deserialize, Serialize)]
 pub struct DbPendingActionEvent {
@@ -33,6 +54,42 @@ pub struct DbPendingActionEvent {
     pub pending_action_type: i32,
     pub retry_count: i32,
     pub relay_details: JsonValue,
+    pub status: String,
+    pub status_timestamps: JsonValue,
+    pub status_reason: Option<String>,
+    pub terminated_at_height: Option<i64>,
+    pub terminated_by_tx_hash: Option<String>,
+}
+
+// why and where a status change happened, recorded on relay_audit_log
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusChange {
+    pub reason: Option<String>,
+    pub carbon_height: Option<i64>,
+    pub carbon_tx_hash: Option<String>,
+}
+
+// append-only history of every status change made by the relayer
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct DbRelayAuditLog {
+    pub id: i64,
+    pub nonce: Option<i64>,
+    pub entity: String,
+    pub entity_id: String,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub reason: Option<String>,
+    pub carbon_height: Option<i64>,
+    pub carbon_tx_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// last block processed by the listeners of a chain, carbon included
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct DbChainCheckpoint {
+    pub chain_id: String,
+    pub last_processed_block: i64,
+    pub updated_at: DateTime<Utc>,
 }
 
 // carbon
@@ -65,7 +122,7 @@ pub struct BridgeRevertedEvent {
 }
 
 // carbon
-#[derive(Debug, Clone, PartialEq, FromRow)]
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
 pub struct DbAxelarCallContractEvent {
     pub id: i32,
     pub nonce: i64,
@@ -88,6 +145,13 @@ pub struct DbContractCallApprovedEvent {
     pub source_tx_hash: String, // hex string
     pub source_event_index: BigDecimal, // Using BigDecimal to represent NUMERIC
     pub payload: String, // hex string
+    pub retry_count: i32,
+    pub execute_tx_hash: Option<String>, // hex string of the last execute tx sent by this relayer
+    pub gas_estimate: Option<i64>, // eth_estimateGas of the last execute, before the safety multiplier
+    pub revert_reason: Option<String>, // decoded revert of the last execute simulation that reverted
+    pub signer_address: Option<String>, // relayer account that sent execute_tx_hash
+    pub symbol: Option<String>, // set for ContractCallApprovedWithMint, executed with executeWithToken
+    pub amount: Option<BigDecimal>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -160,6 +224,94 @@ impl TryFrom<i32> for PendingActionType {
     }
 }
 
+impl RelayStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelayStatus::Observed => "observed",
+            RelayStatus::FeeRejected => "fee_rejected",
+            RelayStatus::Queued => "queued",
+            RelayStatus::RelayStarted => "relay_started",
+            RelayStatus::CallContractSeen => "call_contract_seen",
+            RelayStatus::ApprovedOnEvm => "approved_on_evm",
+            RelayStatus::Executing => "executing",
+            RelayStatus::Executed => "executed",
+            RelayStatus::Failed => "failed",
+            RelayStatus::Expired => "expired",
+            RelayStatus::Reverted => "reverted",
+            RelayStatus::Abandoned => "abandoned",
+        }
+    }
+
+    // statuses where the relay has not been started on carbon yet
+    pub fn unstarted() -> [RelayStatus; 3] {
+        [RelayStatus::Observed, RelayStatus::FeeRejected, RelayStatus::Queued]
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, RelayStatus::Executed | RelayStatus::Expired | RelayStatus::Reverted | RelayStatus::Abandoned)
+    }
+
+    // events can arrive out of order (e.g. a relay started by another relayer), so a status may skip
+    // ahead, but it can never move backwards except for a failed execution going back to be retried
+    pub fn can_transition_to(&self, next: RelayStatus) -> bool {
+        use RelayStatus::*;
+        match (*self, next) {
+            (from, _) if from.is_terminal() => false,
+            (_, Reverted | Abandoned) => true,
+            (Observed | FeeRejected | Queued, FeeRejected | Queued | RelayStarted | CallContractSeen | Expired) => true,
+            (RelayStarted, CallContractSeen | ApprovedOnEvm) => true,
+            (CallContractSeen, ApprovedOnEvm | Executing | Executed) => true,
+            (ApprovedOnEvm, Executing | Executed | Failed) => true,
+            (Executing, ApprovedOnEvm | Executed | Failed) => true,
+            (Failed, ApprovedOnEvm | Executing | Executed) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for RelayStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RelayStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "observed" => Ok(RelayStatus::Observed),
+            "fee_rejected" => Ok(RelayStatus::FeeRejected),
+            "queued" => Ok(RelayStatus::Queued),
+            "relay_started" => Ok(RelayStatus::RelayStarted),
+            "call_contract_seen" => Ok(RelayStatus::CallContractSeen),
+            "approved_on_evm" => Ok(RelayStatus::ApprovedOnEvm),
+            "executing" => Ok(RelayStatus::Executing),
+            "executed" => Ok(RelayStatus::Executed),
+            "failed" => Ok(RelayStatus::Failed),
+            "expired" => Ok(RelayStatus::Expired),
+            "reverted" => Ok(RelayStatus::Reverted),
+            "abandoned" => Ok(RelayStatus::Abandoned),
+            _ => Err(anyhow!("Invalid relay status: {}", s)),
+        }
+    }
+}
+
+impl StatusChange {
+    pub fn reason(reason: impl Into<String>) -> Self {
+        StatusChange {
+            reason: Some(reason.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_carbon_tx(mut self, carbon_height: Option<i64>, carbon_tx_hash: Option<String>) -> Self {
+        self.carbon_height = carbon_height;
+        self.carbon_tx_hash = carbon_tx_hash;
+        self
+    }
+}
+
 impl DbPendingActionEvent {
     pub fn get_relay_details(&self) -> RelayDetails {
         let relay_details_value = serde_json::to_value(&self.relay_details).expect("cannot parse relay_details");
@@ -174,6 +326,10 @@ impl DbPendingActionEvent {
     pub fn get_pending_action_type(&self) -> PendingActionType {
         self.pending_action_type.try_into().unwrap()
     }
+
+    pub fn get_status(&self) -> RelayStatus {
+        RelayStatus::from_str(&self.status).unwrap()
+    }
 }
 
 impl RelayDetails {
@@ -195,3 +351,64 @@ impl RelayDetails {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::RelayStatus::*;
+
+    const ALL: [RelayStatus; 12] = [Observed, FeeRejected, Queued, RelayStarted, CallContractSeen, ApprovedOnEvm, Executing, Executed, Failed, Expired, Reverted, Abandoned];
+
+    #[test]
+    fn relay_status_moves_forward() {
+        assert!(Observed.can_transition_to(Queued));
+        assert!(Queued.can_transition_to(RelayStarted));
+        assert!(RelayStarted.can_transition_to(CallContractSeen));
+        assert!(CallContractSeen.can_transition_to(ApprovedOnEvm));
+        assert!(ApprovedOnEvm.can_transition_to(Executing));
+        assert!(Executing.can_transition_to(Executed));
+        // events can arrive out of order
+        assert!(Observed.can_transition_to(CallContractSeen));
+    }
+
+    #[test]
+    fn relay_status_never_moves_backwards() {
+        assert!(!RelayStarted.can_transition_to(Queued));
+        assert!(!ApprovedOnEvm.can_transition_to(RelayStarted));
+        assert!(!Executing.can_transition_to(Observed));
+        assert!(!Observed.can_transition_to(Executed));
+        assert!(!Queued.can_transition_to(Failed));
+    }
+
+    #[test]
+    fn failed_execution_can_be_retried() {
+        assert!(Failed.can_transition_to(ApprovedOnEvm));
+        assert!(Failed.can_transition_to(Executing));
+        assert!(Executing.can_transition_to(ApprovedOnEvm));
+    }
+
+    #[test]
+    fn terminal_statuses_are_final() {
+        for from in ALL.iter().filter(|status| status.is_terminal()) {
+            for to in ALL {
+                assert!(!from.can_transition_to(to), "{} -> {}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn any_live_status_can_be_reverted_or_abandoned() {
+        for from in ALL.iter().filter(|status| !status.is_terminal()) {
+            assert!(from.can_transition_to(Reverted), "{} -> reverted", from);
+            assert!(from.can_transition_to(Abandoned), "{} -> abandoned", from);
+        }
+    }
+
+    #[test]
+    fn relay_status_round_trips_through_str() {
+        for status in ALL {
+            assert_eq!(RelayStatus::from_str(status.as_str()).unwrap(), status);
+        }
+        assert!(RelayStatus::from_str("unknown").is_err());
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use sqlx::postgres::PgExecutor;
+use crate::db::{DbRelayAuditLog, StatusChange};
+
+pub const ENTITY_PENDING_ACTION_EVENT: &str = "pending_action_event";
+pub const ENTITY_CONTRACT_CALL_APPROVED_EVENT: &str = "contract_call_approved_event";
+
+pub async fn insert_relay_audit_log<'c>(
+    executor: impl PgExecutor<'c>,
+    nonce: Option<i64>,
+    entity: &str,
+    entity_id: &str,
+    from_status: Option<&str>,
+    to_status: &str,
+    change: &StatusChange,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO relay_audit_log (nonce, entity, entity_id, from_status, to_status, reason, carbon_height, carbon_tx_hash) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+        .bind(nonce)
+        .bind(entity)
+        .bind(entity_id)
+        .bind(from_status)
+        .bind(to_status)
+        .bind(&change.reason)
+        .bind(change.carbon_height)
+        .bind(&change.carbon_tx_hash)
+        .execute(executor).await.context("Failed to insert relay_audit_log")?;
+    Ok(())
+}
+
+pub async fn get_relay_audit_logs_by_nonce(pg_pool: Arc<PgPool>, nonce: i64) -> Result<Vec<DbRelayAuditLog>> {
+    sqlx::query_as::<_, DbRelayAuditLog>(
+        "SELECT * FROM relay_audit_log WHERE nonce = $1 ORDER BY id",
+    )
+        .bind(nonce)
+        .fetch_all(pg_pool.as_ref()).await.context("sql query error for relay_audit_log")
+}
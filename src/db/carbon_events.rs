@@ -1,8 +1,10 @@
+use std::str::FromStr;
 use std::sync::Arc;
 use anyhow::{Context,Result};
 use sqlx::PgPool;
-use tracing::{error, info};
-use crate::db::{DbAxelarCallContractEvent, DbPendingActionEvent};
+use tracing::{debug, error, info, warn};
+use crate::db::{DbAxelarCallContractEvent, DbPendingActionEvent, RelayStatus, StatusChange};
+use crate::db::audit_log::{ENTITY_PENDING_ACTION_EVENT, insert_relay_audit_log};
 
 pub async fn get_axelar_call_contract_event(pg_pool: Arc<PgPool>, payload_hash: &String) -> Result<Option<DbAxelarCallContractEvent>> {
     sqlx::query_as::<_, DbAxelarCallContractEvent>(
@@ -20,6 +22,31 @@ pub async fn get_pending_action_by_nonce(pg_pool: Arc<PgPool>, nonce: i64) -> Re
         .fetch_optional(pg_pool.as_ref()).await.context("sql query error for pending_action_events")
 }
 
+pub async fn get_pending_actions(pg_pool: Arc<PgPool>, status: Option<RelayStatus>, limit: i64) -> Result<Vec<DbPendingActionEvent>> {
+    sqlx::query_as::<_, DbPendingActionEvent>(
+        "SELECT * FROM pending_action_events WHERE ($1::text IS NULL OR status = $1) ORDER BY nonce DESC LIMIT $2",
+    )
+        .bind(status.map(|s| s.to_string()))
+        .bind(limit)
+        .fetch_all(pg_pool.as_ref()).await.context("sql query error for pending_action_events")
+}
+
+// number of pending_action_events per relay status
+pub async fn count_pending_actions_by_status(pg_pool: Arc<PgPool>) -> Result<Vec<(String, i64)>> {
+    sqlx::query_as::<_, (String, i64)>(
+        "SELECT status, COUNT(*) FROM pending_action_events GROUP BY status",
+    )
+        .fetch_all(pg_pool.as_ref()).await.context("sql query error for pending_action_events")
+}
+
+pub async fn get_axelar_call_contract_events_by_nonce(pg_pool: Arc<PgPool>, nonce: i64) -> Result<Vec<DbAxelarCallContractEvent>> {
+    sqlx::query_as::<_, DbAxelarCallContractEvent>(
+        "SELECT * FROM axelar_call_contract_events WHERE nonce = $1 ORDER BY id",
+    )
+        .bind(nonce)
+        .fetch_all(pg_pool.as_ref()).await.context("sql query error for axelar_call_contract_events")
+}
+
 pub async fn get_chain_id_for_nonce(pg_pool: Arc<PgPool>, nonce: i64) -> Result<Option<String>> {
     let result = get_pending_action_by_nonce(pg_pool, nonce).await?;
     match result {
@@ -28,21 +55,23 @@ pub async fn get_chain_id_for_nonce(pg_pool: Arc<PgPool>, nonce: i64) -> Result<
     }
 }
 
+// only relays that have not been started can expire
 pub async fn get_expired_pending_action_events(pg_pool: Arc<PgPool>) -> Result<Vec<DbPendingActionEvent>> {
-    sqlx::query_as!(
-        DbPendingActionEvent,
+    sqlx::query_as::<_, DbPendingActionEvent>(
         r#"
         SELECT *
         FROM pending_action_events
         WHERE (relay_details ->> 'expiry_block_time')::timestamp < NOW()
+        AND status = ANY($1)
         "#,
     )
+        .bind(RelayStatus::unstarted().iter().map(|s| s.to_string()).collect::<Vec<String>>())
         .fetch_all(pg_pool.as_ref()).await.context("sql query error for pending_action_events")
 }
 
 pub async fn save_bridge_pending_action_event(pg_pool: Arc<PgPool>, event: &DbPendingActionEvent) {
     let result = sqlx::query!(
-                        "INSERT INTO pending_action_events (connection_id, bridge_id, chain_id, nonce, pending_action_type, retry_count, relay_details) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                        "INSERT INTO pending_action_events (connection_id, bridge_id, chain_id, nonce, pending_action_type, retry_count, relay_details) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (nonce) DO NOTHING",
                         event.connection_id,
                         event.bridge_id,
                         event.chain_id,
@@ -55,7 +84,14 @@ pub async fn save_bridge_pending_action_event(pg_pool: Arc<PgPool>, event: &DbPe
         .await;
 
     match result {
-        Ok(_res) => info!("Saved bridge_pending_action_event with nonce {:?}", event.nonce),
+        // backfill saves every pending nonce again, those that are already saved are skipped
+        Ok(res) if res.rows_affected() == 0 => debug!("bridge_pending_action_event with nonce {:?} is already saved", event.nonce),
+        Ok(_res) => {
+            info!("Saved bridge_pending_action_event with nonce {:?}", event.nonce);
+            if let Err(e) = insert_relay_audit_log(pg_pool.as_ref(), Some(event.nonce), ENTITY_PENDING_ACTION_EVENT, &event.nonce.to_string(), None, RelayStatus::Observed.as_str(), &StatusChange::reason("pending action observed")).await {
+                error!("Failed to audit bridge_pending_action_event with nonce {:?}, err: {:?}", event.nonce, e);
+            }
+        },
         Err(e) => error!("Failed to insert event data: {}", e)
     }
 }
@@ -75,36 +111,76 @@ pub async fn update_bridge_pending_action_event(pg_pool: Arc<PgPool>, event: &Db
     }
 }
 
-pub async fn delete_bridge_pending_action_event(pg_pool: Arc<PgPool>, nonce: i64) {
-    let result = sqlx::query!(
-                        "DELETE FROM pending_action_events where nonce = $1",
-                        nonce,
-                    )
-        .execute(&*pg_pool)
-        .await;
+// soft-deletes pending actions by expiring them, rows are kept with the reason for audit
+pub async fn expire_bridge_pending_action_events(pg_pool: Arc<PgPool>, nonces_to_expire: Vec<i64>, change: &StatusChange) -> Result<()> {
+    info!("Expiring pending_action_events in DB with nonces: {:?}", nonces_to_expire);
 
-    match result {
-        Ok(_res) => info!("deleted bridge_pending_action_event with nonce {:?}", nonce),
-        Err(e) => error!("Failed to delete bridge_pending_action_event, err: {}", e)
+    for nonce in nonces_to_expire {
+        transition_relay_status(pg_pool.clone(), nonce, RelayStatus::Expired, change).await?;
     }
+    Ok(())
 }
 
-pub async fn delete_bridge_pending_action_events(pg_pool: Arc<PgPool>, nonces_to_delete: Vec<i64>) -> Result<()> {
-    let query = format!(
-        "DELETE FROM pending_action_events WHERE nonce IN ({})",
-        nonces_to_delete
-            .iter()
-            .map(|nonce| nonce.to_string())
-            .collect::<Vec<String>>()
-            .join(",")
-    );
+// moves the relay lifecycle of a nonce forward and audits it, returns false if the nonce is not tracked or the transition is not allowed
+pub async fn transition_relay_status(pg_pool: Arc<PgPool>, nonce: i64, to: RelayStatus, change: &StatusChange) -> Result<bool> {
+    let mut tx = pg_pool.begin().await.context("Failed to begin transaction")?;
 
-    info!("Pruning pending_action_events from DB with  nonces: {:?}", nonces_to_delete);
+    // lock the row so that concurrent listeners cannot race each other
+    let current: Option<String> = sqlx::query_scalar(
+        "SELECT status FROM pending_action_events WHERE nonce = $1 FOR UPDATE",
+    )
+        .bind(nonce)
+        .fetch_optional(&mut *tx).await.context("sql query error for pending_action_events")?;
+
+    let current = match current {
+        Some(status) => RelayStatus::from_str(&status)?,
+        None => {
+            debug!("Not transitioning nonce {:?} to {} as it does not exist in DB", nonce, to);
+            return Ok(false)
+        }
+    };
+    if current == to {
+        return Ok(false)
+    }
+    if !current.can_transition_to(to) {
+        warn!("Ignoring invalid relay status transition for nonce {:?}: {} -> {}", nonce, current, to);
+        return Ok(false)
+    }
 
-    let _ = sqlx::query(&query)
-        .execute(pg_pool.as_ref())
-        .await.context("sql delete failed");
-    Ok(())
+    sqlx::query(
+        r#"
+        UPDATE pending_action_events
+        SET status = $1,
+            status_timestamps = status_timestamps || jsonb_build_object($1::text, NOW()),
+            status_reason = $2,
+            terminated_at_height = CASE WHEN $3 THEN $4 ELSE terminated_at_height END,
+            terminated_by_tx_hash = CASE WHEN $3 THEN $5 ELSE terminated_by_tx_hash END
+        WHERE nonce = $6
+        "#,
+    )
+        .bind(to.as_str())
+        .bind(&change.reason)
+        .bind(to.is_terminal())
+        .bind(change.carbon_height)
+        .bind(&change.carbon_tx_hash)
+        .bind(nonce)
+        .execute(&mut *tx).await.context("Failed to update status for pending_action_events")?;
+    insert_relay_audit_log(&mut *tx, Some(nonce), ENTITY_PENDING_ACTION_EVENT, &nonce.to_string(), Some(current.as_str()), to.as_str(), change).await?;
+    tx.commit().await.context("Failed to commit transaction")?;
+
+    info!("Relay status for nonce {:?} transitioned from {} to {}, reason: {:?}", nonce, current, to, &change.reason);
+    Ok(true)
+}
+
+// moves the relay lifecycle forward for the nonce that emitted the payload_hash
+pub async fn transition_relay_status_by_payload_hash(pg_pool: Arc<PgPool>, payload_hash: &String, to: RelayStatus, change: &StatusChange) -> Result<bool> {
+    match get_axelar_call_contract_event(pg_pool.clone(), payload_hash).await? {
+        Some(event) => transition_relay_status(pg_pool, event.nonce, to, change).await,
+        None => {
+            debug!("Not transitioning payload_hash {:?} to {} as it does not exist in DB", payload_hash, to);
+            Ok(false)
+        }
+    }
 }
 
 pub async fn add_bridge_pending_action_event_retry_count(pg_pool: Arc<PgPool>, nonce: i64) -> Result<()> {
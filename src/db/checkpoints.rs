@@ -0,0 +1,27 @@
+use std::sync::Arc;
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use crate::db::DbChainCheckpoint;
+
+// saves the last processed block of a chain, never moving it backwards
+pub async fn save_chain_checkpoint(pg_pool: Arc<PgPool>, chain_id: &str, block: i64) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO chain_checkpoints (chain_id, last_processed_block, updated_at) VALUES ($1, $2, NOW())
+        ON CONFLICT (chain_id) DO UPDATE
+        SET last_processed_block = GREATEST(chain_checkpoints.last_processed_block, EXCLUDED.last_processed_block),
+            updated_at = NOW()
+        "#,
+    )
+        .bind(chain_id)
+        .bind(block)
+        .execute(pg_pool.as_ref()).await.context("Failed to save chain_checkpoints")?;
+    Ok(())
+}
+
+pub async fn get_chain_checkpoints(pg_pool: Arc<PgPool>) -> Result<Vec<DbChainCheckpoint>> {
+    sqlx::query_as::<_, DbChainCheckpoint>(
+        "SELECT * FROM chain_checkpoints ORDER BY chain_id",
+    )
+        .fetch_all(pg_pool.as_ref()).await.context("sql query error for chain_checkpoints")
+}
@@ -1,16 +1,18 @@
 use std::str::FromStr;
 use std::sync::Arc;
-use anyhow::{Context, Error};
+use anyhow::{Context, Error, Result};
 use sqlx::PgPool;
 use sqlx::postgres::PgQueryResult;
 use sqlx::types::BigDecimal;
 use tracing::{debug, error, info, warn};
 use crate::conf::Chain;
-use crate::db::carbon_events::get_axelar_call_contract_event;
-use crate::db::DbContractCallApprovedEvent;
-use crate::util::evm::ContractCallApprovedEvent;
+use crate::db::audit_log::{ENTITY_CONTRACT_CALL_APPROVED_EVENT, insert_relay_audit_log};
+use crate::db::carbon_events::{get_axelar_call_contract_event, transition_relay_status, transition_relay_status_by_payload_hash};
+use crate::db::{DbContractCallApprovedEvent, RelayStatus, StatusChange};
+use crate::util::evm::{ApprovedToken, ContractCallApprovedEvent};
 
-pub async fn save_call_contract_approved_event(chain_config: Chain, pg_pool: Arc<PgPool>, event: ContractCallApprovedEvent) {
+// token is set for ContractCallApprovedWithMint events, which are executed with executeWithToken
+pub async fn save_call_contract_approved_event(chain_config: Chain, pg_pool: Arc<PgPool>, event: ContractCallApprovedEvent, token: Option<ApprovedToken>) {
     let payload_hash = format!("{:?}", event.payload_hash);
 
     // get the corresponding carbon event
@@ -35,24 +37,33 @@ pub async fn save_call_contract_approved_event(chain_config: Chain, pg_pool: Arc
     };
 
     // Save event to db
-    match sqlx::query!(
-                    "INSERT INTO contract_call_approved_events (command_id, blockchain, broadcast_status, source_chain, source_address, contract_address, payload_hash, source_tx_hash, source_event_index, payload) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) ON CONFLICT (payload_hash) DO NOTHING",
-                    format!("{:?}", event.command_id),
-                    chain_config.chain_id,
-                    "pending_broadcast",
-                    event.source_chain,
-                    event.source_address,
-                    format!("{:?}", event.contract_address),
-                    &payload_hash,
-                    format!("{:?}", event.source_tx_hash),
-                    BigDecimal::from_str(&event.source_event_index.to_string()).unwrap(),
-                    &axelar_call_contract_event.payload
+    match sqlx::query(
+                    "INSERT INTO contract_call_approved_events (command_id, blockchain, broadcast_status, source_chain, source_address, contract_address, payload_hash, source_tx_hash, source_event_index, payload, symbol, amount) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) ON CONFLICT (payload_hash) DO NOTHING",
                 )
+        .bind(format!("{:?}", event.command_id))
+        .bind(&chain_config.chain_id)
+        .bind("pending_broadcast")
+        .bind(&event.source_chain)
+        .bind(&event.source_address)
+        .bind(format!("{:?}", event.contract_address))
+        .bind(&payload_hash)
+        .bind(format!("{:?}", event.source_tx_hash))
+        .bind(BigDecimal::from_str(&event.source_event_index.to_string()).unwrap())
+        .bind(&axelar_call_contract_event.payload)
+        .bind(token.as_ref().map(|token| token.symbol.clone()))
+        .bind(token.as_ref().map(|token| BigDecimal::from_str(&token.amount.to_string()).unwrap()))
         .execute(&*pg_pool)
         .await {
         Ok(result) => {
             if result.rows_affected() > 0 {
                 info!("Inserted ContractCallApprovedEvent event successfully for carbon_axelar_gateway ({:?}) with payload_hash {}, event: {:?}", &chain_config.carbon_axelar_gateway, &payload_hash, event);
+                let change = StatusChange::reason(format!("ContractCallApproved seen on {}", &chain_config.chain_id));
+                if let Err(e) = insert_relay_audit_log(pg_pool.as_ref(), Some(axelar_call_contract_event.nonce), ENTITY_CONTRACT_CALL_APPROVED_EVENT, &payload_hash, None, "pending_broadcast", &change).await {
+                    error!("Failed to audit ContractCallApprovedEvent with payload_hash {}, err: {:?}", &payload_hash, e);
+                }
+                if let Err(e) = transition_relay_status(pg_pool.clone(), axelar_call_contract_event.nonce, RelayStatus::ApprovedOnEvm, &change).await {
+                    error!("Failed to transition relay status for nonce {:?}, err: {:?}", axelar_call_contract_event.nonce, e);
+                }
             }
         },
         Err(e) => error!("Unable to insert event, err {}:", e),
@@ -60,11 +71,144 @@ pub async fn save_call_contract_approved_event(chain_config: Chain, pg_pool: Arc
 }
 
 pub async fn update_broadcast_status(pg_pool: Arc<PgPool>, event: &DbContractCallApprovedEvent, status: &str) -> std::result::Result<PgQueryResult, Error> {
-    sqlx::query!(
-                        "UPDATE contract_call_approved_events SET broadcast_status = $1 WHERE id = $2",
-                        status,
-                        &event.id
-                    )
+    let change = StatusChange::reason(format!("broadcast status on {} changed to {}", &event.blockchain, status));
+    update_broadcast_status_with_change(pg_pool, event, status, &change).await
+}
+
+pub async fn update_broadcast_status_with_change(pg_pool: Arc<PgPool>, event: &DbContractCallApprovedEvent, status: &str, change: &StatusChange) -> std::result::Result<PgQueryResult, Error> {
+    set_broadcast_status(pg_pool, event, None, status, change).await
+}
+
+// moves the event to status only if it is still in from_status, returns false if it was moved by someone else first
+pub async fn transition_broadcast_status(pg_pool: Arc<PgPool>, event: &DbContractCallApprovedEvent, from_status: &str, status: &str, change: &StatusChange) -> Result<bool> {
+    let result = set_broadcast_status(pg_pool, event, Some(from_status), status, change).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+async fn set_broadcast_status(pg_pool: Arc<PgPool>, event: &DbContractCallApprovedEvent, expected_status: Option<&str>, status: &str, change: &StatusChange) -> std::result::Result<PgQueryResult, Error> {
+    let mut tx = pg_pool.begin().await.context("Failed to begin transaction")?;
+
+    // lock the row so that the audited from_status is accurate
+    let from_status: Option<String> = sqlx::query_scalar(
+        "SELECT broadcast_status FROM contract_call_approved_events WHERE id = $1 FOR UPDATE",
+    )
+        .bind(event.id)
+        .fetch_optional(&mut *tx).await.context("sql query error for contract_call_approved_events")?;
+
+    let result = sqlx::query(
+        "UPDATE contract_call_approved_events SET broadcast_status = $1 WHERE id = $2 AND ($3::text IS NULL OR broadcast_status = $3)",
+    )
+        .bind(status)
+        .bind(event.id)
+        .bind(expected_status)
+        .execute(&mut *tx)
+        .await.context("Failed to update contract_call_approved_events")?;
+    if result.rows_affected() == 0 {
+        return Ok(result)
+    }
+
+    if from_status.as_deref() != Some(status) {
+        let nonce = get_axelar_call_contract_event(pg_pool.clone(), &event.payload_hash).await?.map(|e| e.nonce);
+        insert_relay_audit_log(&mut *tx, nonce, ENTITY_CONTRACT_CALL_APPROVED_EVENT, &event.payload_hash, from_status.as_deref(), status, change).await?;
+    }
+    tx.commit().await.context("Failed to commit transaction")?;
+
+    // keep the relay lifecycle of the nonce in step with its execution
+    if let Some(relay_status) = relay_status_for_broadcast_status(status) {
+        if let Err(e) = transition_relay_status_by_payload_hash(pg_pool.clone(), &event.payload_hash, relay_status, change).await {
+            error!("Failed to transition relay status for payload_hash {:?}, err: {:?}", &event.payload_hash, e);
+        }
+    }
+    Ok(result)
+}
+
+pub async fn add_contract_call_approved_event_retry_count(pg_pool: Arc<PgPool>, id: i32) -> Result<()> {
+    sqlx::query("UPDATE contract_call_approved_events SET retry_count = retry_count + 1 WHERE id = $1")
+        .bind(id)
         .execute(pg_pool.as_ref())
-        .await.context("Failed to update contract_call_approved_events")
+        .await.context("Failed to add retry count for contract_call_approved_events")?;
+    Ok(())
+}
+
+// raises the retry count to the maximum so that reconciliation leaves the event failed
+pub async fn exhaust_contract_call_approved_event_retries(pg_pool: Arc<PgPool>, id: i32, max_retry_count: i32) -> Result<()> {
+    sqlx::query("UPDATE contract_call_approved_events SET retry_count = GREATEST(retry_count, $1) WHERE id = $2")
+        .bind(max_retry_count)
+        .bind(id)
+        .execute(pg_pool.as_ref())
+        .await.context("Failed to exhaust retry count for contract_call_approved_events")?;
+    Ok(())
+}
+
+// saved as soon as the tx is sent so that it can be looked up if the relayer stops before the receipt is seen
+pub async fn save_execute_tx_hash(pg_pool: Arc<PgPool>, id: i32, tx_hash: &str, signer_address: &str) -> Result<()> {
+    sqlx::query("UPDATE contract_call_approved_events SET execute_tx_hash = $1, signer_address = $2 WHERE id = $3")
+        .bind(tx_hash)
+        .bind(signer_address)
+        .bind(id)
+        .execute(pg_pool.as_ref())
+        .await.context("Failed to save execute_tx_hash for contract_call_approved_events")?;
+    Ok(())
+}
+
+pub async fn save_gas_estimate(pg_pool: Arc<PgPool>, id: i32, gas_estimate: i64) -> Result<()> {
+    sqlx::query("UPDATE contract_call_approved_events SET gas_estimate = $1 WHERE id = $2")
+        .bind(gas_estimate)
+        .bind(id)
+        .execute(pg_pool.as_ref())
+        .await.context("Failed to save gas_estimate for contract_call_approved_events")?;
+    Ok(())
+}
+
+pub async fn save_revert_reason(pg_pool: Arc<PgPool>, id: i32, revert_reason: &str) -> Result<()> {
+    sqlx::query("UPDATE contract_call_approved_events SET revert_reason = $1 WHERE id = $2")
+        .bind(revert_reason)
+        .bind(id)
+        .execute(pg_pool.as_ref())
+        .await.context("Failed to save revert_reason for contract_call_approved_events")?;
+    Ok(())
+}
+
+fn relay_status_for_broadcast_status(broadcast_status: &str) -> Option<RelayStatus> {
+    match broadcast_status {
+        "pending_broadcast" => Some(RelayStatus::ApprovedOnEvm),
+        "broadcasting" => Some(RelayStatus::Executing),
+        "executed" => Some(RelayStatus::Executed),
+        "failed" => Some(RelayStatus::Failed),
+        _ => None,
+    }
+}
+
+pub async fn get_contract_call_approved_event_by_id(pg_pool: Arc<PgPool>, id: i32) -> Result<Option<DbContractCallApprovedEvent>> {
+    sqlx::query_as::<_, DbContractCallApprovedEvent>(
+        "SELECT * FROM contract_call_approved_events WHERE id = $1",
+    )
+        .bind(id)
+        .fetch_optional(pg_pool.as_ref()).await.context("sql query error for contract_call_approved_events")
+}
+
+pub async fn get_contract_call_approved_events(pg_pool: Arc<PgPool>, broadcast_status: Option<&str>, blockchain: Option<&str>, limit: i64) -> Result<Vec<DbContractCallApprovedEvent>> {
+    sqlx::query_as::<_, DbContractCallApprovedEvent>(
+        "SELECT * FROM contract_call_approved_events WHERE ($1::text IS NULL OR broadcast_status = $1) AND ($2::text IS NULL OR blockchain = $2) ORDER BY id DESC LIMIT $3",
+    )
+        .bind(broadcast_status)
+        .bind(blockchain)
+        .bind(limit)
+        .fetch_all(pg_pool.as_ref()).await.context("sql query error for contract_call_approved_events")
+}
+
+pub async fn get_contract_call_approved_events_by_payload_hashes(pg_pool: Arc<PgPool>, payload_hashes: &[String]) -> Result<Vec<DbContractCallApprovedEvent>> {
+    sqlx::query_as::<_, DbContractCallApprovedEvent>(
+        "SELECT * FROM contract_call_approved_events WHERE payload_hash = ANY($1) ORDER BY id",
+    )
+        .bind(payload_hashes)
+        .fetch_all(pg_pool.as_ref()).await.context("sql query error for contract_call_approved_events")
+}
+
+// number of contract_call_approved_events per blockchain and broadcast_status
+pub async fn count_contract_call_approved_events(pg_pool: Arc<PgPool>) -> Result<Vec<(String, String, i64)>> {
+    sqlx::query_as::<_, (String, String, i64)>(
+        "SELECT blockchain, broadcast_status, COUNT(*) FROM contract_call_approved_events GROUP BY blockchain, broadcast_status",
+    )
+        .fetch_all(pg_pool.as_ref()).await.context("sql query error for contract_call_approved_events")
 }
@@ -1,2 +1,5 @@
 pub mod listener;
-pub mod broadcaster;
\ No newline at end of file
+pub mod broadcaster;
+pub mod gas;
+pub mod nonce;
+pub mod revert;
\ No newline at end of file
@@ -1,27 +1,49 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::panic::AssertUnwindSafe;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use anyhow::{Context, Result};
-use ethers::core::k256::ecdsa::SigningKey;
 use ethers::prelude::*;
-use ethers::signers::LocalWallet;
+// the trait is shadowed by the Signer struct below
+use ethers::signers::Signer as _;
+use ethers::types::transaction::eip2718::TypedTransaction;
 use ethers::utils::hex::decode;
+use futures::FutureExt;
 use sqlx::PgPool;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Mutex};
 use tokio::sync::mpsc::{Receiver, Sender};
-use tokio::time::{Duration, interval, sleep, timeout};
+use tokio::task::JoinSet;
+use tokio::time::{Duration, Instant, interval, sleep, timeout};
 use tracing::{debug, error, info, instrument, warn};
 
-use crate::conf::Chain;
-use crate::db::DbContractCallApprovedEvent;
-use crate::db::evm_events::{update_broadcast_status};
+use crate::conf::{Chain, Supervisor};
+use crate::evm::gas::{GasPricing, TxType};
+use crate::evm::nonce::NonceManager;
+use crate::evm::revert::{decode_revert_reason, is_permanent_revert, SimulationReverted};
+use crate::db::{DbContractCallApprovedEvent, StatusChange};
+use crate::db::evm_events::{add_contract_call_approved_event_retry_count, exhaust_contract_call_approved_event_retries, get_contract_call_approved_events, save_execute_tx_hash, save_gas_estimate, save_revert_reason, transition_broadcast_status, update_broadcast_status, update_broadcast_status_with_change};
+use crate::health;
+use crate::shutdown;
+use crate::metrics::{EVM_EXECUTES, record_gas_spent};
+use crate::signer::evm::{EvmProvider, load_signers};
+use crate::supervisor::{supervise, wait_all};
 
+// the errors are the ones the carbon gateway can revert with, including those of the contracts it inherits
 abigen!(
     IAxelarExecutable,
     r#"[
         execute(bytes32,string,string,bytes)()
         executeWithToken(bytes32,string,string,bytes,string,uint256)()
+        error NotApprovedByGateway()
+        error InvalidAddress()
+        error EnforcedPause()
+        error ReentrancyGuardReentrantCall()
+        error SafeERC20FailedOperation(address)
+        error ERC20InsufficientBalance(address,uint256,uint256)
+        error ERC20InsufficientAllowance(address,uint256,uint256)
     ]"#
 );
 
@@ -29,27 +51,38 @@ abigen!(
     IAxelarGateway,
     r#"[
         isContractCallApproved(bytes32,string,string,address,bytes32)(bool)
+        isContractCallAndMintApproved(bytes32,string,string,address,bytes32,string,uint256)(bool)
     ]"#
 );
 
 #[instrument(name = "broadcaster_evm", skip_all)]
-pub async fn init_all(evm_chains: Vec<Chain>, pg_pool: Arc<PgPool>) {
+pub async fn init_all(evm_chains: Vec<Chain>, pg_pool: Arc<PgPool>, supervisor_conf: Supervisor) -> Result<()> {
     let evm_chains_clone = evm_chains.clone();
     // initialize signature providers for each chain
-    let channel_tx_map = init_channels(evm_chains_clone, pg_pool.clone()).await;
+    let (channel_tx_map, tasks) = init_channels(evm_chains_clone, pg_pool.clone(), supervisor_conf);
 
-    // listen for db events that have not been broadcast
+    // listen for db events that have not been broadcast, until a broadcaster has failed too many times
     let pg_pool_clone = pg_pool.clone();
-    poll_for_new_events(pg_pool_clone, channel_tx_map).await;
+    tokio::select! {
+        _ = poll_for_new_events(pg_pool_clone, channel_tx_map) => Ok(()),
+        result = wait_all(tasks) => result,
+    }
 }
 
 // Polls for new contract_call_approved_events saved in the DB that can be executed and enqueues them into the broadcast channel
 #[instrument(name = "poll_for_new_events", skip_all)]
 async fn poll_for_new_events(pool: Arc<PgPool>, channel_tx_map: HashMap<String, Sender<DbContractCallApprovedEvent>>) {
     info!("Watching for events to broadcast");
+    let _guard = health::task_started("evm_broadcaster_poller");
     let mut interval = interval(Duration::from_secs(5));
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown::requested() => {
+                info!("Shutting down, no longer queueing events for broadcast");
+                return
+            }
+        }
         if let Err(e) = queue_new_events_for_broadcast(&pool, channel_tx_map.clone()).await {
             error!("Failed to queue new events for broadcast: {}", e);
         }
@@ -61,11 +94,10 @@ async fn queue_new_events_for_broadcast(pool: &PgPool, channel_tx_map: HashMap<S
     Sender<DbContractCallApprovedEvent>>) -> Result<()> {
     // check for new events
     debug!("Checking for new events...");
-    let events: Vec<DbContractCallApprovedEvent> = sqlx::query_as!(
-        DbContractCallApprovedEvent,
+    let events: Vec<DbContractCallApprovedEvent> = sqlx::query_as::<_, DbContractCallApprovedEvent>(
         "SELECT * FROM contract_call_approved_events WHERE broadcast_status = $1",
-        "pending_broadcast"
     )
+        .bind("pending_broadcast")
         .fetch_all(pool)
         .await?;
 
@@ -85,113 +117,440 @@ async fn queue_new_events_for_broadcast(pool: &PgPool, channel_tx_map: HashMap<S
     Ok(())
 }
 
-async fn init_channels(evm_chains: Vec<Chain>, pg_pool: Arc<PgPool>) -> HashMap<String, Sender<DbContractCallApprovedEvent>> {
+fn init_channels(evm_chains: Vec<Chain>, pg_pool: Arc<PgPool>, supervisor_conf: Supervisor) -> (HashMap<String, Sender<DbContractCallApprovedEvent>>, JoinSet<Result<()>>) {
     let mut channels = HashMap::new();
+    let mut tasks = JoinSet::new();
     // Initialize providers and channels for each chain
     for chain in evm_chains {
         info!("Initializing receive_and_broadcast for {:?}", &chain.chain_id);
-        // init channel
+        // init channel, the receiver is shared so that a restarted broadcaster keeps draining the same channel
         let (tx, rx) = mpsc::channel::<DbContractCallApprovedEvent>(100); // Adjust the size based on expected load
         channels.insert(chain.chain_id.clone(), tx);
-        let pg_pool = pg_pool.clone();
+        let rx = Arc::new(Mutex::new(rx));
 
-        // spawn receiving logic
-        tokio::spawn(async move {
-            if let Err(e) = receive_and_broadcast(chain, rx, pg_pool).await {
-                // Handle or log the error e
-                error!("Error in receive_and_broadcast: {:?}", e);
+        // spawn supervised receiving logic
+        let name = format!("evm_broadcaster:{}", &chain.chain_id);
+        let pg_pool = pg_pool.clone();
+        tasks.spawn(supervise(name.clone(), supervisor_conf.clone(), move || {
+            let name = name.clone();
+            let chain = chain.clone();
+            let rx = rx.clone();
+            let pg_pool = pg_pool.clone();
+            async move {
+                let _guard = health::task_started(&name);
+                let mut rx = rx.lock().await;
+                receive_and_broadcast(chain, &mut rx, pg_pool).await
+                    .context("Error in receive_and_broadcast")
             }
-        });
+        }));
     }
-    channels
+    (channels, tasks)
 }
 
 
 #[instrument(name = "broadcaster_evm::receive_and_broadcast", skip_all, fields(
     chain = chain.chain_id
 ))]
-pub async fn receive_and_broadcast(chain: Chain, mut rx: Receiver<DbContractCallApprovedEvent>, pg_pool: Arc<PgPool>) -> Result<()> {
-    let provider = init_provider(chain.clone()).await?;
+pub async fn receive_and_broadcast(chain: Chain, rx: &mut Receiver<DbContractCallApprovedEvent>, pg_pool: Arc<PgPool>) -> Result<()> {
+    let providers = init_providers(chain.clone()).await?;
+    // reads go through the first relayer account
+    let provider = providers[0].clone();
     let axelar_gateway = chain.axelar_gateway_proxy.parse::<Address>()?;
     let axelar_gateway = IAxelarGateway::new(axelar_gateway, provider.clone());
 
-    Ok(while let Some(event) = rx.recv().await {
-        let command_id = H256::from_str(&event.command_id).expect("Failed to parse command_id");
-        let contract_address = Address::from_str(&event.contract_address).expect("Failed to parse contract_address");
-        let payload_hash = H256::from_str(&event.payload_hash).expect("Failed to parse payload_hash");
+    let mut signers = Vec::new();
+    for provider in providers {
+        // txs left stuck in the mempool by a previous run block every new execute, replace them before starting
+        recover_stuck_nonces(&chain, provider.clone()).await?;
+        let nonces = NonceManager::new(provider.as_ref(), provider.address()).await?;
+        signers.push(Arc::new(Signer { provider, nonces, in_flight: AtomicUsize::new(0) }));
+    }
+    let max_in_flight = chain.max_in_flight_executes.unwrap_or(1).max(1);
+    let mut next_signer = 0;
+    let mut broadcasts = JoinSet::new();
+    // ids of the events being broadcasted, the poller queues pending_broadcast events again until they are claimed
+    let mut in_flight_ids = HashSet::new();
 
-        // Query blockchain to check if the contract call has already been approved
-        let is_approved = axelar_gateway.is_contract_call_approved(
-            command_id.0,
-            event.source_chain.clone(),
-            event.source_address.clone(),
-            contract_address,
-            payload_hash.0,
-        )
-            .call()
-            .await
-            .unwrap_or(false);
-        if !is_approved {
-            // If already executed, mark db event as executed
-            info!("Skipping event as blockchain query for is_contract_call_approved is !approved. This can mean it is already executed, payload_hash: {:?}", &event.payload_hash);
-            // update executed
-            update_broadcast_status(pg_pool.clone(), &event, "executed").await?;
-            continue;
+    // rows left in broadcasting or failed are reconciled on start and then periodically, the first tick is immediate
+    let mut reconcile_interval = interval(Duration::from_secs(chain.reconcile_poll_frequency.unwrap_or(300)));
+
+    loop {
+        // stop taking new events once shutdown is requested, the events being broadcasted are finished first
+        let event = tokio::select! {
+            _ = reconcile_interval.tick() => {
+                // wait for in-flight executes so that none of them is reconciled or has its nonce taken for a gap
+                while broadcasts.join_next().await.is_some() {}
+                in_flight_ids.clear();
+                for signer in &signers {
+                    if let Err(e) = fill_nonce_gaps(&chain, signer.provider.clone(), &signer.nonces).await {
+                        error!("Failed to fill nonce gaps of {:?}: {:?}", signer.provider.address(), e);
+                    }
+                }
+                if let Err(e) = reconcile_events(&chain, &axelar_gateway, provider.clone(), pg_pool.clone()).await {
+                    error!("Failed to reconcile events: {:?}", e);
+                }
+                continue
+            }
+            Some(result) = broadcasts.join_next(), if !broadcasts.is_empty() => {
+                if let Ok(id) = result {
+                    in_flight_ids.remove(&id);
+                }
+                continue
+            }
+            event = rx.recv(), if broadcasts.len() < max_in_flight * signers.len() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = shutdown::requested() => break,
+        };
+        // a duplicate would be sent by another signer with its own nonce, so it is not ordered behind the first
+        if !in_flight_ids.insert(event.id) {
+            debug!("Skipping event {:?} as it is already being broadcasted", event.id);
+            continue
         }
+        // round-robin over the signers that have room for another execute
+        let signer = (0..signers.len())
+            .map(|i| &signers[(next_signer + i) % signers.len()])
+            .find(|signer| signer.in_flight.load(Ordering::SeqCst) < max_in_flight);
+        let Some(signer) = signer else {
+            // the poller queues the event again while it is still pending_broadcast
+            warn!("No signer has room for event {:?}, skipping it for now", event.id);
+            in_flight_ids.remove(&event.id);
+            continue
+        };
+        let signer = SignerSlot::take(signer.clone());
+        next_signer = (next_signer + 1) % signers.len();
+        let in_flight = shutdown::in_flight();
+        let chain = chain.clone();
+        let axelar_gateway = axelar_gateway.clone();
+        let pg_pool = pg_pool.clone();
+        let event_id = event.id;
+        broadcasts.spawn(async move {
+            let _in_flight = in_flight;
+            // a panic is caught so that the event id is still returned and released
+            match AssertUnwindSafe(execute_event(chain, event, &axelar_gateway, signer.provider.clone(), &signer.nonces, pg_pool)).catch_unwind().await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => error!("Failed to execute event: {:?}", e),
+                Err(_) => error!("Execute of event {:?} panicked", event_id),
+            }
+            event_id
+        });
+    }
+    while broadcasts.join_next().await.is_some() {}
+    Ok(())
+}
+
+// a relayer account of the chain with its own nonces
+struct Signer {
+    provider: Arc<EvmProvider>,
+    nonces: NonceManager,
+    in_flight: AtomicUsize,
+}
+
+// counts an execute against its signer until dropped, also when the execute panics
+struct SignerSlot(Arc<Signer>);
+
+impl SignerSlot {
+    fn take(signer: Arc<Signer>) -> Self {
+        signer.in_flight.fetch_add(1, Ordering::SeqCst);
+        SignerSlot(signer)
+    }
+}
+
+impl std::ops::Deref for SignerSlot {
+    type Target = Signer;
+
+    fn deref(&self) -> &Signer {
+        &self.0
+    }
+}
 
-        // Double check db to make sure it is still pending_broadcast
-        let exists = sqlx::query!(
-                "SELECT EXISTS(SELECT 1 FROM contract_call_approved_events WHERE id = $1 AND broadcast_status = 'pending_broadcast')",
-                event.id.clone()
-            )
-            .fetch_one(pg_pool.as_ref())
-            .await?
-            .exists.unwrap_or(false);
-        if !exists {
-            info!("Skipping event as it is not pending: {:?}", &event.id);
-            continue;
+impl Drop for SignerSlot {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// returned when an execute tx was sent but not mined, and can no longer be replaced. it can still be mined,
+// so the event is left in broadcasting for reconciliation instead of being sent again with a new nonce
+#[derive(Debug)]
+pub struct ExecuteStillPending {
+    // unknown when sending the tx timed out, the node may still have received it
+    pub tx_hash: Option<H256>,
+    pub reason: String,
+}
+
+impl fmt::Display for ExecuteStillPending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.tx_hash {
+            Some(tx_hash) => write!(f, "execute tx {:?} is still pending: {}", tx_hash, self.reason),
+            None => write!(f, "execute tx may still be pending: {}", self.reason),
         }
+    }
+}
+
+impl std::error::Error for ExecuteStillPending {}
+
+async fn execute_event(chain: Chain, event: DbContractCallApprovedEvent, axelar_gateway: &IAxelarGateway<EvmProvider>, provider: Arc<EvmProvider>, nonces: &NonceManager, pg_pool: Arc<PgPool>) -> Result<()> {
+    // Query blockchain to check if the contract call has already been approved.
+    // a failed query leaves the event in pending_broadcast so that it is picked up again
+    let is_approved = is_call_approved(axelar_gateway, &event).await?;
+    if !is_approved {
+        // If already executed, mark db event as executed
+        info!("Skipping event as blockchain query for is_contract_call_approved is !approved. This can mean it is already executed, payload_hash: {:?}", &event.payload_hash);
+        // update executed
+        update_broadcast_status(pg_pool.clone(), &event, "executed").await?;
+        return Ok(());
+    }
+
+    // claim the event by moving it to broadcasting, only one broadcaster can move it out of pending_broadcast
+    let change = StatusChange::reason(format!("broadcast status on {} changed to broadcasting", &event.blockchain));
+    let claimed = transition_broadcast_status(pg_pool.clone(), &event, "pending_broadcast", "broadcasting", &change).await?;
+    if !claimed {
+        info!("Skipping event as it is not pending: {:?}", &event.id);
+        return Ok(());
+    }
 
-        // Update to broadcasting
-        update_broadcast_status(pg_pool.clone(), &event, "broadcasting").await?;
-
-        // Attempt to broadcast the transaction
-        match broadcast_tx(chain.clone(), event.clone(), provider.clone()).await {
-            Ok(_) => {
-                info!("broadcast success");
-                // If broadcast_tx succeeds, update the execution status
-                if let Err(e) = update_broadcast_status(pg_pool.clone(), &event, "executed").await {
-                    // Handle the error from update_executed if necessary
-                    error!("Failed to update executed status: {:?}", e);
+    // Attempt to broadcast the transaction
+    match broadcast_tx(chain.clone(), event.clone(), provider.clone(), nonces, Some(pg_pool.clone())).await {
+        Ok(_) => {
+            info!("broadcast success");
+            EVM_EXECUTES.with_label_values(&[&chain.chain_id, "succeeded"]).inc();
+            // If broadcast_tx succeeds, update the execution status
+            if let Err(e) = update_broadcast_status(pg_pool.clone(), &event, "executed").await {
+                // Handle the error from update_executed if necessary
+                error!("Failed to update executed status: {:?}", e);
+            }
+        }
+        Err(e) if e.downcast_ref::<ExecuteStillPending>().is_some() => {
+            warn!("Leaving event {:?} in broadcasting for reconciliation: {:#}", &event.id, e);
+        }
+        Err(e) => {
+            // Handle the error from broadcast_tx
+            error!("Failed to broadcast transaction: {:?}", e);
+            let reverted = e.downcast_ref::<SimulationReverted>();
+            let outcome = if reverted.is_some() { "reverted" } else { "failed" };
+            EVM_EXECUTES.with_label_values(&[&chain.chain_id, outcome]).inc();
+            // a permanent revert is not retried by reconciliation, other failures are until maximum_execute_retry_count
+            if reverted.is_some_and(|reverted| reverted.permanent) {
+                if let Err(e) = exhaust_contract_call_approved_event_retries(pg_pool.clone(), event.id, chain.maximum_execute_retry_count.unwrap_or(3)).await {
+                    error!("Failed to stop retries of event {:?}: {:?}", &event.id, e);
                 }
             }
-            Err(e) => {
-                // Handle the error from broadcast_tx
-                error!("Failed to broadcast transaction: {:?}", e);
-                // If broadcast_tx fails, update the execution status
-                if let Err(e) = update_broadcast_status(pg_pool.clone(), &event, "failed").await {
-                    // Handle the error from update_executed if necessary
-                    error!("Failed to update executed status: {:?}", e);
+            // If broadcast_tx fails, update the execution status
+            let change = StatusChange::reason(format!("broadcast on {} failed: {:#}", &event.blockchain, e));
+            if let Err(e) = update_broadcast_status_with_change(pg_pool.clone(), &event, "failed", &change).await {
+                // Handle the error from update_executed if necessary
+                error!("Failed to update executed status: {:?}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+// after a crash, txs of the relayer can be left in the mempool at a gas price that is too low to be mined.
+// they are given the receipt timeout to be mined, then replaced with cancel txs. their rows are left in
+// broadcasting and retried with new nonces by reconciliation
+async fn recover_stuck_nonces(chain: &Chain, provider: Arc<EvmProvider>) -> Result<()> {
+    let address = provider.address();
+    let pending = provider.get_transaction_count(address, Some(BlockNumber::Pending.into())).await
+        .context("Failed to get pending transaction count")?;
+    let deadline = Instant::now() + Duration::from_secs(chain.gas_policy.receipt_timeout_secs);
+    loop {
+        let latest = provider.get_transaction_count(address, Some(BlockNumber::Latest.into())).await
+            .context("Failed to get transaction count")?;
+        if latest >= pending {
+            return Ok(())
+        }
+        if Instant::now() >= deadline {
+            warn!("{} txs of {:?} are stuck from nonce {}, replacing them with cancel txs", pending - latest, address, latest);
+            let mut nonce = latest;
+            while nonce < pending {
+                send_cancel_tx(chain, provider.clone(), nonce).await?;
+                nonce += U256::one();
+            }
+            return Ok(())
+        }
+        sleep(Duration::from_secs(5)).await;
+    }
+}
+
+async fn fill_nonce_gaps(chain: &Chain, provider: Arc<EvmProvider>, nonces: &NonceManager) -> Result<()> {
+    for nonce in nonces.gaps(provider.as_ref()).await? {
+        warn!("Nonce {} of {:?} was never mined, filling it with a cancel tx", nonce, nonces.address());
+        send_cancel_tx(chain, provider.clone(), nonce).await?;
+    }
+    Ok(())
+}
+
+// a 0 value transfer to the relayer itself, priced to replace whatever tx is at the nonce
+async fn send_cancel_tx(chain: &Chain, provider: Arc<EvmProvider>, nonce: U256) -> Result<()> {
+    let policy = &chain.gas_policy;
+    let gas_pricing = GasPricing::fetch(provider.as_ref(), chain.tx_type).await?
+        .bumped(policy.bump_percent)
+        .capped(policy.max_gas_price()?);
+    let address = provider.address();
+    let mut tx: TypedTransaction = match chain.tx_type {
+        TxType::Legacy => TransactionRequest::new().to(address).value(0).nonce(nonce).gas(21000).into(),
+        TxType::Eip1559 => Eip1559TransactionRequest::new().to(address).value(0).nonce(nonce).gas(21000).into(),
+    };
+    gas_pricing.apply(&mut tx);
+
+    info!("Sending cancel tx from {:?} with nonce {} and {}", address, nonce, gas_pricing);
+    let pending_tx = match provider.send_transaction(tx, None).await {
+        Ok(pending_tx) => pending_tx,
+        // the nonce was mined in the meantime
+        Err(e) if e.to_string().contains("nonce too low") => return Ok(()),
+        Err(e) => return Err(e).context("Failed to send cancel tx"),
+    };
+    match timeout(Duration::from_secs(policy.receipt_timeout_secs), pending_tx).await {
+        Ok(Ok(Some(receipt))) => {
+            record_gas_spent(&chain.chain_id, &receipt);
+            info!("Cancel tx {:?} for nonce {} mined", receipt.transaction_hash, nonce);
+            Ok(())
+        }
+        Ok(Ok(None)) => anyhow::bail!("Cancel tx for nonce {} was dropped", nonce),
+        Ok(Err(e)) => Err(e).context("Failed to await cancel tx receipt"),
+        Err(_) => anyhow::bail!("Cancel tx for nonce {} was not mined in time", nonce),
+    }
+}
+
+// reconciles rows left in broadcasting (e.g. after a crash) or failed with the chain:
+// - executed if the relayer's last execute tx succeeded or the call is no longer approved on the gateway
+// - left as is while the last execute tx is still pending in the mempool
+// - otherwise back to pending_broadcast until maximum_execute_retry_count is reached, after which they stay failed
+async fn reconcile_events(chain: &Chain, axelar_gateway: &IAxelarGateway<EvmProvider>, provider: Arc<EvmProvider>, pg_pool: Arc<PgPool>) -> Result<()> {
+    let max_retry_count = chain.maximum_execute_retry_count.unwrap_or(3);
+    for broadcast_status in ["broadcasting", "failed"] {
+        let events = get_contract_call_approved_events(pg_pool.clone(), Some(broadcast_status), Some(&chain.chain_id), 1000).await?;
+        for event in events {
+            let status = match reconciled_status(axelar_gateway, provider.clone(), &event).await {
+                Ok(ReconciledStatus::Executed(reason)) => {
+                    info!("Reconciling event {:?} from {} to executed: {}", event.id, broadcast_status, reason);
+                    update_broadcast_status_with_change(pg_pool.clone(), &event, "executed", &StatusChange::reason(reason)).await?;
+                    continue
+                }
+                Ok(ReconciledStatus::Pending(tx_hash)) => {
+                    debug!("Event {:?} stays in {} as its execute tx {:?} is still pending", event.id, broadcast_status, tx_hash);
+                    continue
+                }
+                Ok(ReconciledStatus::Approved) => {
+                    if event.retry_count >= max_retry_count {
+                        "failed"
+                    } else {
+                        "pending_broadcast"
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to reconcile event {:?}: {:?}", event.id, e);
+                    continue
+                }
+            };
+            if status == broadcast_status {
+                debug!("Event {:?} has reached the maximum execute retry count of {}", event.id, max_retry_count);
+                continue
+            }
+            info!("Reconciling event {:?} from {} to {}, retry {}/{}", event.id, broadcast_status, status, event.retry_count, max_retry_count);
+            if status == "pending_broadcast" {
+                add_contract_call_approved_event_retry_count(pg_pool.clone(), event.id).await?;
+            }
+            let change = StatusChange::reason(format!("reconciled from {} on {}", broadcast_status, &chain.chain_id));
+            update_broadcast_status_with_change(pg_pool.clone(), &event, status, &change).await?;
+        }
+    }
+    Ok(())
+}
+
+enum ReconciledStatus {
+    Executed(String),
+    // the last execute tx is still in the mempool, sending another one would execute twice if both are mined
+    Pending(H256),
+    Approved,
+}
+
+async fn reconciled_status(axelar_gateway: &IAxelarGateway<EvmProvider>, provider: Arc<EvmProvider>, event: &DbContractCallApprovedEvent) -> Result<ReconciledStatus> {
+    // look for the execute tx sent by this relayer
+    if let Some(tx_hash) = &event.execute_tx_hash {
+        let tx_hash = H256::from_str(tx_hash).context("Failed to parse execute_tx_hash")?;
+        let receipt = provider.get_transaction_receipt(tx_hash).await.context("Failed to get execute tx receipt")?;
+        match receipt {
+            Some(receipt) if receipt.status == Some(U64::from(1)) => {
+                return Ok(ReconciledStatus::Executed(format!("execute tx {:?} succeeded", receipt.transaction_hash)))
+            }
+            Some(_) => {}
+            None => {
+                let tx = provider.get_transaction(tx_hash).await.context("Failed to get execute tx")?;
+                if tx.is_some() {
+                    return Ok(ReconciledStatus::Pending(tx_hash))
                 }
             }
         }
-    })
+    }
+
+    let is_approved = is_call_approved(axelar_gateway, event).await?;
+    if !is_approved {
+        return Ok(ReconciledStatus::Executed("contract call is no longer approved on the gateway".to_string()))
+    }
+    Ok(ReconciledStatus::Approved)
+}
+
+// symbol and amount of the token minted with a ContractCallApprovedWithMint
+fn approved_token(event: &DbContractCallApprovedEvent) -> Result<Option<(String, U256)>> {
+    match (&event.symbol, &event.amount) {
+        (Some(symbol), Some(amount)) => {
+            let amount = U256::from_dec_str(&amount.with_scale(0).to_string()).context("Failed to parse amount")?;
+            Ok(Some((symbol.clone(), amount)))
+        }
+        _ => Ok(None),
+    }
+}
+
+// isContractCallAndMintApproved for calls approved with a token, isContractCallApproved otherwise
+async fn is_call_approved(axelar_gateway: &IAxelarGateway<EvmProvider>, event: &DbContractCallApprovedEvent) -> Result<bool> {
+    let command_id = H256::from_str(&event.command_id)?.0;
+    let contract_address = Address::from_str(&event.contract_address)?;
+    let payload_hash = H256::from_str(&event.payload_hash)?.0;
+    let call = match approved_token(event)? {
+        Some((symbol, amount)) => axelar_gateway.is_contract_call_and_mint_approved(
+            command_id,
+            event.source_chain.clone(),
+            event.source_address.clone(),
+            contract_address,
+            payload_hash,
+            symbol,
+            amount,
+        ),
+        None => axelar_gateway.is_contract_call_approved(
+            command_id,
+            event.source_chain.clone(),
+            event.source_address.clone(),
+            contract_address,
+            payload_hash,
+        ),
+    };
+    call.call().await.context("Failed to query is_contract_call_approved")
+}
+
+// provider of the first relayer key of the chain
+pub async fn init_provider(chain: Chain) -> Result<Arc<EvmProvider>> {
+    Ok(init_providers(chain).await?.remove(0))
 }
 
-pub async fn init_provider(chain: Chain) -> Result<Arc<SignerMiddleware<Provider<Http>, Wallet<SigningKey>>>> {
-    let provider = Provider::<Http>::try_from(chain.rpc_url)
+// one provider per relayer key of the chain
+pub async fn init_providers(chain: Chain) -> Result<Vec<Arc<EvmProvider>>> {
+    let provider = Provider::<Http>::try_from(chain.rpc_url.clone())
         .context("Failed to connect to the network")?;
     let chain_id = provider.get_chainid().await
         .context("Failed to get chain ID")?;
-    let wallet = chain.relayer_private_key.parse::<LocalWallet>()
-        .context("Error parsing wallet key")?;
-    let wallet = wallet.with_chain_id(chain_id.as_u64());
-    let provider = Arc::new(SignerMiddleware::new(provider, wallet));
-    Ok(provider)
+    let signers = load_signers(&chain).await?;
+    Ok(signers.into_iter().map(|signer| {
+        let signer = signer.with_chain_id(chain_id.as_u64());
+        Arc::new(SignerMiddleware::new(provider.clone(), signer))
+    }).collect())
 }
 
 #[instrument(skip_all, fields(payload_hash = event.payload_hash))]
-pub async fn broadcast_tx(chain: Chain, event: DbContractCallApprovedEvent, provider: Arc<SignerMiddleware<Provider<Http>, Wallet<SigningKey>>>) -> Result<()> {
+pub async fn broadcast_tx(chain: Chain, event: DbContractCallApprovedEvent, provider: Arc<EvmProvider>, nonces: &NonceManager, pg_pool: Option<Arc<PgPool>>) -> Result<()> {
     let executable = chain.carbon_axelar_gateway.parse::<Address>()?;
     let executable = IAxelarExecutable::new(executable, provider.clone());
 
@@ -202,62 +561,160 @@ pub async fn broadcast_tx(chain: Chain, event: DbContractCallApprovedEvent, prov
     let payload_bytes = decode(&event.payload)?;
 
 
-    // get nonce
-    let nonce = provider.get_transaction_count(provider.address(), None).await?;
+    // get current gas price, never above the chain's cap
+    let policy = &chain.gas_policy;
+    let max_gas_price = policy.max_gas_price()?;
+    let market_pricing = GasPricing::fetch(provider.as_ref(), chain.tx_type).await?;
+    let mut gas_pricing = market_pricing.capped(max_gas_price);
+    if gas_pricing != market_pricing {
+        warn!("Market gas price ({}) is above the cap of {} gwei, sending with {}", market_pricing, policy.max_gas_price_gwei, gas_pricing);
+    }
+    // calls approved with a minted token are executed with executeWithToken
+    let token = approved_token(&event)?;
+    let execute_call = || match &token {
+        Some((symbol, amount)) => executable.execute_with_token(
+            command_id_h256.0,
+            event.source_chain.clone(),
+            event.source_address.clone(),
+            Bytes::from(payload_bytes.clone()),
+            symbol.clone(),
+            *amount,
+        ),
+        None => executable.execute(
+            command_id_h256.0,
+            event.source_chain.clone(),
+            event.source_address.clone(),
+            Bytes::from(payload_bytes.clone()),
+        ),
+    };
+    let call = execute_call();
+
+    // simulate first so that no gas is paid for an execute that would revert.
+    // other errors (e.g. rpc failures) are transient and the execute is still sent
+    if let Err(e) = call.call().await {
+        let revert_data = match e.as_revert() {
+            Some(data) => Some(data.clone()),
+            // some nodes do not return the revert data of a bare revert, or return it for temporary state
+            None if e.to_string().contains("execution reverted") => Some(Bytes::new()),
+            None => None,
+        };
+        match revert_data {
+            Some(data) => {
+                let reason = decode_revert_reason(&data);
+                let permanent = is_permanent_revert(&data);
+                warn!("Execute simulation for payload_hash {} reverted ({}): {}", &event.payload_hash, if permanent { "permanent" } else { "retryable" }, reason);
+                if let Some(pg_pool) = &pg_pool {
+                    if let Err(e) = save_revert_reason(pg_pool.clone(), event.id, &reason).await {
+                        error!("Failed to save revert reason: {:?}", e);
+                    }
+                }
+                return Err(SimulationReverted { reason, permanent }.into())
+            }
+            None => warn!("Failed to simulate execute for payload_hash {}, sending it anyway: {:?}", &event.payload_hash, e),
+        }
+    }
 
-    // get current gas price
-    let mut gas_price = provider.get_gas_price().await?;
-    gas_price = gas_price;
-    // hardcode max retries TODO: remove hardcode
-    let max_retries = 5;
+    // estimate once, replacements send the same call
+    let gas_limit = match call.estimate_gas().await {
+        Ok(gas_estimate) => {
+            if let Some(pg_pool) = &pg_pool {
+                if let Err(e) = save_gas_estimate(pg_pool.clone(), event.id, gas_estimate.as_u64() as i64).await {
+                    error!("Failed to save gas estimate: {:?}", e);
+                }
+            }
+            let gas_limit = policy.gas_limit(gas_estimate);
+            info!("Estimated gas {} for payload_hash {}, using gas limit {}", gas_estimate, &event.payload_hash, gas_limit);
+            gas_limit
+        }
+        Err(e) => {
+            warn!("Failed to estimate gas for payload_hash {}, using the configured gas_limit {}: {:?}", &event.payload_hash, chain.gas_limit, e);
+            U256::from(chain.gas_limit)
+        }
+    };
+    // taken after simulation and estimation so that a reverting execute does not use up a nonce
+    let mut nonce = nonces.next();
     let mut retries = 0;
+    // every tx sent with the nonce, any of them can still be mined after the retries are given up
+    let mut sent_tx_hashes = Vec::new();
+    // a send that timed out may have reached the node, so its nonce is kept and the event is not failed
+    let mut maybe_sent = false;
 
     loop {
         // Send the transaction with the current gas price
-        let tx = executable
-            .execute(
-                command_id_h256.0,
-                event.source_chain.clone(),
-                event.source_address.clone(),
-                Bytes::from(payload_bytes.clone()),
-            )
-            .gas_price(gas_price.clone())
-            .nonce(nonce.clone())
-            .gas(chain.gas_limit);
-
-        info!("Sending execute tx from {} for command id {}, payload_hash: {}, with evm gas price: {}, evm nonce: {}", provider.address(), event.command_id.clone(), event.payload_hash.clone(), gas_price.clone(), nonce.clone());
-
-        let send_timeout = Duration::from_secs(60);
-        match timeout(send_timeout, tx.send()).await {
-            Ok(Ok(pending_tx)) => match timeout(send_timeout, pending_tx).await {
-                Ok(Ok(Some(receipt))) => {
-                    if receipt.status == Some(U64::from(1)) {
-                        info!(
-                        "Transaction for payload_hash {} successfully executed. tx_hash: {:?}",
-                        &event.payload_hash,
-                        &receipt.transaction_hash
-                    );
-                        debug!("Transaction receipt: {receipt:?}");
-                        return Ok(());
-                    } else {
-                        error!("Transaction failed with receipt: {receipt:?}");
-                        anyhow::bail!("Transaction failed with receipt: {receipt:?}");
+        let mut tx = execute_call()
+            .nonce(nonce.nonce())
+            .gas(gas_limit);
+        if chain.tx_type == TxType::Legacy {
+            tx = tx.legacy();
+        }
+        gas_pricing.apply(&mut tx.tx);
+
+        info!("Sending execute tx from {} for command id {}, payload_hash: {}, with evm {}, evm nonce: {}", provider.address(), event.command_id.clone(), event.payload_hash.clone(), gas_pricing, nonce.nonce());
+
+        match timeout(Duration::from_secs(policy.send_timeout_secs), tx.send()).await {
+            Ok(Ok(pending_tx)) => {
+                nonce.mark_used();
+                sent_tx_hashes.push(pending_tx.tx_hash());
+                // keep the hash of the latest attempt so that the tx can be found by reconciliation
+                if let Some(pg_pool) = &pg_pool {
+                    if let Err(e) = save_execute_tx_hash(pg_pool.clone(), event.id, &format!("{:?}", pending_tx.tx_hash()), &format!("{:?}", provider.address())).await {
+                        error!("Failed to save execute tx hash: {:?}", e);
                     }
                 }
-                Ok(Ok(None)) => {
-                    warn!("Transaction receipt not found. Retrying with higher gas price.");
-                }
-                Ok(Err(e)) => {
-                    error!("Failed to await transaction receipt.");
-                    return Err(e).context("Failed to await transaction receipt");
-                }
-                Err(_) => {
-                    error!("Awaiting transaction receipt timed out.");
+                match timeout(Duration::from_secs(policy.receipt_timeout_secs), pending_tx).await {
+                    Ok(Ok(Some(receipt))) => {
+                        record_gas_spent(&chain.chain_id, &receipt);
+                        if receipt.status == Some(U64::from(1)) {
+                            info!(
+                            "Transaction for payload_hash {} successfully executed. tx_hash: {:?}",
+                            &event.payload_hash,
+                            &receipt.transaction_hash
+                        );
+                            debug!("Transaction receipt: {receipt:?}");
+                            return Ok(());
+                        } else {
+                            error!("Transaction failed with receipt: {receipt:?}");
+                            anyhow::bail!("Transaction failed with receipt: {receipt:?}");
+                        }
+                    }
+                    Ok(Ok(None)) => {
+                        warn!("Transaction receipt not found. Retrying with higher gas price.");
+                    }
+                    Ok(Err(e)) => {
+                        error!("Failed to await transaction receipt.");
+                        return Err(e).context("Failed to await transaction receipt");
+                    }
+                    Err(_) => {
+                        error!("Awaiting transaction receipt timed out.");
+                    }
                 }
-            },
+            }
             Ok(Err(e)) => {
                 if e.to_string().contains("already known") {
                     warn!("Transaction already known. Retrying with higher gas price.");
+                } else if e.to_string().contains("nonce too low") {
+                    nonce.mark_used();
+                    // a replacement is rejected like this once an earlier attempt has been mined
+                    if !sent_tx_hashes.is_empty() {
+                        return match mined_receipt(provider.as_ref(), &sent_tx_hashes).await? {
+                            Some(receipt) if receipt.status == Some(U64::from(1)) => {
+                                record_gas_spent(&chain.chain_id, &receipt);
+                                info!("Transaction for payload_hash {} was executed by an earlier attempt. tx_hash: {:?}", &event.payload_hash, &receipt.transaction_hash);
+                                Ok(())
+                            }
+                            Some(receipt) => {
+                                record_gas_spent(&chain.chain_id, &receipt);
+                                anyhow::bail!("Transaction failed with receipt: {receipt:?}")
+                            }
+                            None => Err(ExecuteStillPending {
+                                tx_hash: sent_tx_hashes.last().copied(),
+                                reason: "nonce too low for a replacement but no attempt was mined yet".to_string(),
+                            }.into()),
+                        }
+                    }
+                    // the nonce was used outside of the nonce manager
+                    nonces.sync(provider.as_ref()).await?;
+                    return Err(e).context("Failed to send transaction, nonce too low");
                 } else {
                     error!("Failed to send transaction.");
                     return Err(e).context("Failed to send transaction");
@@ -265,19 +722,44 @@ pub async fn broadcast_tx(chain: Chain, event: DbContractCallApprovedEvent, prov
             }
             Err(_) => {
                 error!("Sending transaction timed out.");
+                nonce.mark_used();
+                maybe_sent = true;
             }
         };
 
         // the above code should early return if there was a successful tx or an irrecoverable failure.
         // so, if we have reached this point, that means we need to retry
-        if retries < max_retries {
+        if retries < policy.max_retries {
             retries += 1;
-            // hardcode increase by 20% TODO: remove hardcode
-            gas_price = gas_price * U256::from(12) / U256::from(10);
-            warn!("Retrying transaction with higher gas price: {:?}, in 30s", gas_price);
-            sleep(Duration::from_secs(30)).await;
+            // the replacement must outbid the stuck tx, and follow the market if it moved further
+            let market_pricing = GasPricing::fetch(provider.as_ref(), chain.tx_type).await.unwrap_or(gas_pricing);
+            let replacement_pricing = gas_pricing.bumped(policy.bump_percent).max(&market_pricing).capped(max_gas_price);
+            // the stuck tx stays in the mempool and is picked up by reconciliation if it is mined later
+            if !replacement_pricing.can_replace(&gas_pricing) {
+                let reason = format!("gas price cap of {} gwei reached, not sending a replacement", policy.max_gas_price_gwei);
+                if !sent_tx_hashes.is_empty() || maybe_sent {
+                    return Err(ExecuteStillPending { tx_hash: sent_tx_hashes.last().copied(), reason }.into())
+                }
+                anyhow::bail!("Transaction not mined and {}", reason);
+            }
+            gas_pricing = replacement_pricing;
+            warn!("Retrying transaction with higher gas price: {}, in {}s", gas_pricing, policy.resubmit_interval_secs);
+            sleep(Duration::from_secs(policy.resubmit_interval_secs)).await;
         } else {
+            if !sent_tx_hashes.is_empty() || maybe_sent {
+                return Err(ExecuteStillPending { tx_hash: sent_tx_hashes.last().copied(), reason: "max retries reached".to_string() }.into())
+            }
             anyhow::bail!("Sending transaction timed out and max retries reached.");
         }
     }
-}
\ No newline at end of file
+}
+
+// the receipt of whichever of the txs sent with the same nonce was mined
+async fn mined_receipt(provider: &EvmProvider, tx_hashes: &[H256]) -> Result<Option<TransactionReceipt>> {
+    for tx_hash in tx_hashes {
+        if let Some(receipt) = provider.get_transaction_receipt(*tx_hash).await.context("Failed to get execute tx receipt")? {
+            return Ok(Some(receipt))
+        }
+    }
+    Ok(None)
+}
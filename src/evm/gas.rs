@@ -0,0 +1,273 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use serde::Deserialize;
+
+use crate::conf::GasPolicy;
+
+// nodes reject replacements (same nonce) that do not raise the fees by at least 10%
+const MIN_REPLACEMENT_BUMP_PERCENT: u64 = 10;
+// number of blocks and reward percentile used with eth_feeHistory
+const FEE_HISTORY_BLOCKS: u64 = 10;
+const FEE_HISTORY_PRIORITY_PERCENTILE: f64 = 50.0;
+// used when eth_feeHistory returns no rewards, e.g. on chains with empty blocks
+const FALLBACK_PRIORITY_FEE_GWEI: u64 = 1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxType {
+    #[default]
+    Legacy,
+    Eip1559,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GasPricing {
+    Legacy { gas_price: U256 },
+    Eip1559 { max_fee_per_gas: U256, max_priority_fee_per_gas: U256 },
+}
+
+impl fmt::Display for GasPricing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GasPricing::Legacy { gas_price } => write!(f, "gas_price: {}", gas_price),
+            GasPricing::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } =>
+                write!(f, "max_fee_per_gas: {}, max_priority_fee_per_gas: {}", max_fee_per_gas, max_priority_fee_per_gas),
+        }
+    }
+}
+
+impl GasPricing {
+    // current market price, from eth_gasPrice for legacy txs and eth_feeHistory for eip-1559 txs
+    pub async fn fetch<M: Middleware>(provider: &M, tx_type: TxType) -> Result<Self>
+        where M::Error: 'static
+    {
+        match tx_type {
+            TxType::Legacy => {
+                let gas_price = provider.get_gas_price().await.context("Failed to get gas price")?;
+                Ok(GasPricing::Legacy { gas_price })
+            }
+            TxType::Eip1559 => {
+                let fee_history = provider
+                    .fee_history(FEE_HISTORY_BLOCKS, BlockNumber::Latest, &[FEE_HISTORY_PRIORITY_PERCENTILE])
+                    .await
+                    .context("Failed to get fee history")?;
+                // the last base fee is the one of the next block
+                let base_fee = fee_history.base_fee_per_gas.last().copied().unwrap_or_default();
+                let mut rewards: Vec<U256> = fee_history.reward.iter()
+                    .filter_map(|reward| reward.first().copied())
+                    .filter(|reward| !reward.is_zero())
+                    .collect();
+                rewards.sort();
+                let max_priority_fee_per_gas = rewards.get(rewards.len() / 2).copied()
+                    .unwrap_or_else(|| ethers::utils::parse_units(FALLBACK_PRIORITY_FEE_GWEI, "gwei").unwrap().into());
+                // leave room for the base fee to double before the tx is no longer includable
+                let max_fee_per_gas = base_fee * 2 + max_priority_fee_per_gas;
+                Ok(GasPricing::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas })
+            }
+        }
+    }
+
+    // fees for a replacement tx, raised by at least the minimum that nodes accept for a replacement
+    pub fn bumped(&self, bump_percent: u64) -> Self {
+        let bump = |value: U256| {
+            let percent = U256::from(100 + bump_percent.max(MIN_REPLACEMENT_BUMP_PERCENT));
+            // round up so that the replacement is never below the node's threshold
+            (value * percent + 99) / 100
+        };
+        match *self {
+            GasPricing::Legacy { gas_price } => GasPricing::Legacy { gas_price: bump(gas_price) },
+            GasPricing::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => GasPricing::Eip1559 {
+                max_fee_per_gas: bump(max_fee_per_gas),
+                max_priority_fee_per_gas: bump(max_priority_fee_per_gas),
+            },
+        }
+    }
+
+    // the higher of both fees, so that a replacement follows the market if it has moved more than the bump
+    pub fn max(&self, other: &Self) -> Self {
+        match (*self, *other) {
+            (GasPricing::Legacy { gas_price: a }, GasPricing::Legacy { gas_price: b }) => GasPricing::Legacy { gas_price: a.max(b) },
+            (GasPricing::Eip1559 { max_fee_per_gas: a_fee, max_priority_fee_per_gas: a_tip },
+                GasPricing::Eip1559 { max_fee_per_gas: b_fee, max_priority_fee_per_gas: b_tip }) => GasPricing::Eip1559 {
+                max_fee_per_gas: a_fee.max(b_fee),
+                max_priority_fee_per_gas: a_tip.max(b_tip),
+            },
+            (pricing, _) => pricing,
+        }
+    }
+
+    // both fees limited to the cap
+    pub fn capped(&self, cap: U256) -> Self {
+        match *self {
+            GasPricing::Legacy { gas_price } => GasPricing::Legacy { gas_price: gas_price.min(cap) },
+            GasPricing::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => GasPricing::Eip1559 {
+                max_fee_per_gas: max_fee_per_gas.min(cap),
+                max_priority_fee_per_gas: max_priority_fee_per_gas.min(cap),
+            },
+        }
+    }
+
+    // whether a tx with these fees would be accepted by nodes as a replacement of a tx with the previous fees
+    pub fn can_replace(&self, previous: &Self) -> bool {
+        let min_replacement = previous.bumped(MIN_REPLACEMENT_BUMP_PERCENT);
+        match (*self, min_replacement) {
+            (GasPricing::Legacy { gas_price: a }, GasPricing::Legacy { gas_price: b }) => a >= b,
+            (GasPricing::Eip1559 { max_fee_per_gas: a_fee, max_priority_fee_per_gas: a_tip },
+                GasPricing::Eip1559 { max_fee_per_gas: b_fee, max_priority_fee_per_gas: b_tip }) => a_fee >= b_fee && a_tip >= b_tip,
+            _ => false,
+        }
+    }
+
+    // the most that can be paid per gas
+    pub fn max_gas_price(&self) -> U256 {
+        match *self {
+            GasPricing::Legacy { gas_price } => gas_price,
+            GasPricing::Eip1559 { max_fee_per_gas, .. } => max_fee_per_gas,
+        }
+    }
+
+    pub fn apply(&self, tx: &mut TypedTransaction) {
+        match (*self, tx) {
+            (GasPricing::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas }, TypedTransaction::Eip1559(inner)) => {
+                inner.max_fee_per_gas = Some(max_fee_per_gas);
+                inner.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+            }
+            (pricing, tx) => {
+                tx.set_gas_price(pricing.max_gas_price());
+            }
+        }
+    }
+}
+
+impl GasPolicy {
+    // gas limit for an estimate, with room for state changes between estimation and inclusion
+    pub fn gas_limit(&self, estimate: U256) -> U256 {
+        let limit = estimate.as_u128() as f64 * self.gas_limit_multiplier;
+        U256::from(limit.ceil() as u128).clamp(self.min_gas_limit.into(), self.max_gas_limit.into())
+    }
+
+    // the most an execute priced at the market can cost, after every replacement bump and at the largest gas limit
+    // it can be sent with. the chain's gas_limit is used when estimation fails, so it is an upper bound as well
+    pub fn max_execute_cost(&self, market_pricing: GasPricing, fallback_gas_limit: u64) -> Result<U256> {
+        let cap = self.max_gas_price()?;
+        let mut pricing = market_pricing.capped(cap);
+        for _ in 0..self.max_retries {
+            pricing = pricing.bumped(self.bump_percent).capped(cap);
+        }
+        let gas_limit = U256::from(self.max_gas_limit.max(fallback_gas_limit));
+        Ok(pricing.max_gas_price() * gas_limit)
+    }
+
+    pub fn max_gas_price(&self) -> Result<U256> {
+        let cap = ethers::utils::parse_units(self.max_gas_price_gwei.to_string(), "gwei")
+            .with_context(|| format!("Invalid max_gas_price_gwei {}", self.max_gas_price_gwei))?;
+        Ok(cap.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gwei(value: u64) -> U256 {
+        U256::from(value) * U256::exp10(9)
+    }
+
+    #[test]
+    fn gas_limit_is_estimate_times_multiplier() {
+        let policy = GasPolicy { gas_limit_multiplier: 1.5, min_gas_limit: 0, max_gas_limit: 10_000_000, ..GasPolicy::default() };
+        assert_eq!(policy.gas_limit(U256::from(200_000)), U256::from(300_000));
+        // rounded up
+        assert_eq!(policy.gas_limit(U256::from(3)), U256::from(5));
+    }
+
+    #[test]
+    fn gas_limit_is_bounded() {
+        let policy = GasPolicy { gas_limit_multiplier: 2.0, min_gas_limit: 100_000, max_gas_limit: 1_000_000, ..GasPolicy::default() };
+        assert_eq!(policy.gas_limit(U256::from(1_000)), U256::from(100_000));
+        assert_eq!(policy.gas_limit(U256::from(900_000)), U256::from(1_000_000));
+    }
+
+    #[test]
+    fn default_gas_policy_is_valid() {
+        assert!(GasPolicy::default().validate().is_ok());
+    }
+
+    #[test]
+    fn gas_policy_rejects_min_above_max_gas_limit() {
+        let policy = GasPolicy { min_gas_limit: 2_000_000, max_gas_limit: 1_000_000, ..GasPolicy::default() };
+        assert!(policy.validate().is_err());
+    }
+
+    #[test]
+    fn gas_policy_rejects_invalid_values() {
+        assert!(GasPolicy { gas_limit_multiplier: 0.0, ..GasPolicy::default() }.validate().is_err());
+        assert!(GasPolicy { gas_limit_multiplier: f64::NAN, ..GasPolicy::default() }.validate().is_err());
+        assert!(GasPolicy { max_gas_price_gwei: 0.0, ..GasPolicy::default() }.validate().is_err());
+        assert!(GasPolicy { max_gas_price_gwei: -1.0, ..GasPolicy::default() }.validate().is_err());
+        assert!(GasPolicy { bump_percent: 0, ..GasPolicy::default() }.validate().is_err());
+        assert!(GasPolicy { bump_percent: 500, ..GasPolicy::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn max_gas_price_is_in_wei() {
+        let policy = GasPolicy { max_gas_price_gwei: 1.5, ..GasPolicy::default() };
+        assert_eq!(policy.max_gas_price().unwrap(), U256::from(1_500_000_000u64));
+        assert_eq!(GasPolicy::default().max_gas_price().unwrap(), gwei(500));
+    }
+
+    #[test]
+    fn bump_is_at_least_the_replacement_minimum() {
+        let pricing = GasPricing::Legacy { gas_price: gwei(100) };
+        assert_eq!(pricing.bumped(20), GasPricing::Legacy { gas_price: gwei(120) });
+        // nodes reject replacements below 10%
+        assert_eq!(pricing.bumped(5), GasPricing::Legacy { gas_price: gwei(110) });
+        // rounded up
+        assert_eq!(GasPricing::Legacy { gas_price: U256::from(15) }.bumped(10), GasPricing::Legacy { gas_price: U256::from(17) });
+    }
+
+    #[test]
+    fn bump_raises_both_eip1559_fees() {
+        let pricing = GasPricing::Eip1559 { max_fee_per_gas: gwei(100), max_priority_fee_per_gas: gwei(2) };
+        assert_eq!(pricing.bumped(50), GasPricing::Eip1559 { max_fee_per_gas: gwei(150), max_priority_fee_per_gas: gwei(3) });
+    }
+
+    #[test]
+    fn capped_limits_every_fee() {
+        assert_eq!(GasPricing::Legacy { gas_price: gwei(600) }.capped(gwei(500)), GasPricing::Legacy { gas_price: gwei(500) });
+        assert_eq!(GasPricing::Legacy { gas_price: gwei(100) }.capped(gwei(500)), GasPricing::Legacy { gas_price: gwei(100) });
+        let pricing = GasPricing::Eip1559 { max_fee_per_gas: gwei(600), max_priority_fee_per_gas: gwei(700) };
+        assert_eq!(pricing.capped(gwei(500)), GasPricing::Eip1559 { max_fee_per_gas: gwei(500), max_priority_fee_per_gas: gwei(500) });
+    }
+
+    #[test]
+    fn bumps_stop_replacing_at_the_cap() {
+        let cap = gwei(130);
+        let pricing = GasPricing::Legacy { gas_price: gwei(100) };
+        let replacement = pricing.bumped(20).capped(cap);
+        assert!(replacement.can_replace(&pricing));
+        // the next bump is cut to 130, less than 10% above 120
+        let next = replacement.bumped(20).capped(cap);
+        assert_eq!(next, GasPricing::Legacy { gas_price: cap });
+        assert!(!next.can_replace(&replacement));
+    }
+
+    #[test]
+    fn eip1559_replacement_needs_both_fees_bumped() {
+        let pricing = GasPricing::Eip1559 { max_fee_per_gas: gwei(100), max_priority_fee_per_gas: gwei(2) };
+        assert!(pricing.bumped(10).can_replace(&pricing));
+        let fee_only = GasPricing::Eip1559 { max_fee_per_gas: gwei(200), max_priority_fee_per_gas: gwei(2) };
+        assert!(!fee_only.can_replace(&pricing));
+        assert!(!GasPricing::Legacy { gas_price: gwei(200) }.can_replace(&pricing));
+    }
+
+    #[test]
+    fn max_follows_the_market() {
+        let ours = GasPricing::Eip1559 { max_fee_per_gas: gwei(120), max_priority_fee_per_gas: gwei(1) };
+        let market = GasPricing::Eip1559 { max_fee_per_gas: gwei(100), max_priority_fee_per_gas: gwei(3) };
+        assert_eq!(ours.max(&market), GasPricing::Eip1559 { max_fee_per_gas: gwei(120), max_priority_fee_per_gas: gwei(3) });
+    }
+}
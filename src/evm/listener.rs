@@ -9,41 +9,59 @@ use ethers::{
     providers::{Provider},
 };
 use ethers::abi::RawLog;
-use ethers::utils::keccak256;
 use sqlx::PgPool;
+use tokio::task::JoinSet;
 use tracing::{debug, error, info, instrument};
 
-use crate::conf::Chain;
-use crate::constants::events::EVM_CONTRACT_CALL_APPROVED_EVENT;
+use crate::conf::{Chain, Supervisor};
+use crate::constants::events::{EVM_CONTRACT_CALL_APPROVED_EVENT, EVM_CONTRACT_CALL_APPROVED_WITH_MINT_EVENT};
+use crate::db::checkpoints::save_chain_checkpoint;
 use crate::db::evm_events::save_call_contract_approved_event;
-use crate::util::evm::ContractCallApprovedEvent;
+use crate::health;
+use crate::supervisor::{supervise, wait_all};
+use crate::util::evm::{ContractCallApprovedEvent, ContractCallApprovedWithMintEvent};
 
+// spawns a supervised listener and backfiller for each chain, only returns once a task has failed too many times
 #[instrument(name = "listener_evm", skip_all)]
-pub async fn init_all(evm_chains: Vec<Chain>, pg_pool: Arc<PgPool>) {
+pub async fn init_all(evm_chains: Vec<Chain>, pg_pool: Arc<PgPool>, supervisor_conf: Supervisor) -> Result<()> {
+    let mut tasks = JoinSet::new();
     for chain in evm_chains {
+        info!("Subscribing to {}, hasWS: {}", &chain.chain_id, &chain.has_ws);
+        let name = format!("evm_listener:{}", &chain.chain_id);
         let pg_pool_clone = pg_pool.clone();
         let chain_clone = chain.clone();
-        info!("Subscribing to {}, hasWS: {}", &chain.chain_id, &chain.has_ws);
-        tokio::spawn(async move {
-            if chain_clone.has_ws {
-                if let Err(e) = init_ws(chain_clone.clone(), pg_pool_clone).await {
-                    error!("Error initializing WebSocket for {}: {}", &chain_clone.ws_url, e);
-                }
-            } else {
-                if let Err(e) = init_http(chain_clone.clone(), pg_pool_clone).await {
-                    error!("Error initializing Http client for {}: {}", &chain_clone.rpc_url, e);
+        tasks.spawn(supervise(name.clone(), supervisor_conf.clone(), move || {
+            let name = name.clone();
+            let chain = chain_clone.clone();
+            let pg_pool = pg_pool_clone.clone();
+            async move {
+                let _guard = health::task_started(&name);
+                if chain.has_ws {
+                    init_ws(chain.clone(), pg_pool).await
+                        .with_context(|| format!("Error initializing WebSocket for {}", &chain.ws_url))
+                } else {
+                    init_http(chain.clone(), pg_pool).await
+                        .with_context(|| format!("Error initializing Http client for {}", &chain.rpc_url))
                 }
             }
-        });
+        }));
+
+        info!("Initializing http backfiller for {}", &chain.chain_id);
+        let name = format!("evm_backfiller:{}", &chain.chain_id);
         let pg_pool_clone = pg_pool.clone();
         let chain_clone = chain.clone();
-        info!("Initializing http backfiller for {}", &chain_clone.chain_id);
-        tokio::spawn(async move {
-            if let Err(e) = init_backfiller(chain_clone.clone(), pg_pool_clone).await {
-                error!("Error initializing Http client for {}: {}", &chain_clone.rpc_url, e);
+        tasks.spawn(supervise(name.clone(), supervisor_conf.clone(), move || {
+            let name = name.clone();
+            let chain = chain_clone.clone();
+            let pg_pool = pg_pool_clone.clone();
+            async move {
+                let _guard = health::task_started(&name);
+                init_backfiller(chain.clone(), pg_pool).await
+                    .with_context(|| format!("Error initializing Http client for {}", &chain.rpc_url))
             }
-        });
+        }));
     }
+    wait_all(tasks).await
 }
 
 // init_ws connect to the evm network via WebSocket and watch for relevant events
@@ -63,13 +81,10 @@ async fn init_ws(chain_config: Chain, pg_pool: Arc<PgPool>) -> Result<()> {
     let topic2 = H256::from(chain_config.carbon_axelar_gateway.clone().parse::<Address>()?);
 
     // Use an infinite loop to create (and re-create) the subscription if needed.
+    let filter = Filter::new().address(address).topic0(approved_event_topics()).topic2(topic2);
     loop {
         // Create a new subscription stream.
-        let event = ContractCallApprovedEvent::new::<_, Provider<Ws>>(
-            Filter::new().address(address.clone()).topic2(topic2),
-            Arc::clone(&provider),
-        );
-        let mut events = match event.subscribe().await {
+        let mut logs = match provider.subscribe_logs(&filter).await {
             Ok(stream) => {
                 info!(
                     "Subscribed to events on chain {} filtered by carbon contract: {:?}",
@@ -84,26 +99,13 @@ async fn init_ws(chain_config: Chain, pg_pool: Arc<PgPool>) -> Result<()> {
             }
         };
 
-        // Process events until the stream ends or an error is encountered.
-        while let Some(log) = events.next().await {
-            match log {
-                Ok(event) => {
-                    let chain_config = chain_config.clone();
-                    let pg_pool = pg_pool.clone();
-                    tokio::spawn(async move {
-                        info!(
-                            "Received ContractCallApprovedEvent for carbon_axelar_gateway ({:?}): {:?}",
-                            &chain_config.carbon_axelar_gateway, event
-                        );
-                        save_call_contract_approved_event(chain_config, pg_pool, event).await;
-                    });
-                }
-                Err(e) => {
-                    error!("Error listening for ContractCallApprovedEvent logs: {:?}", e);
-                    // Break the inner loop to trigger re-subscription.
-                    break;
-                }
-            }
+        // Process events until the stream ends.
+        while let Some(log) = logs.next().await {
+            let chain_config = chain_config.clone();
+            let pg_pool = pg_pool.clone();
+            tokio::spawn(async move {
+                save_approved_log(chain_config, pg_pool, log).await;
+            });
         }
 
         // If the event subscription stream ended (e.g. filter not found), wait a moment and try to re-subscribe.
@@ -128,26 +130,17 @@ async fn init_http(chain_config: Chain, pg_pool: Arc<PgPool>) -> Result<()> {
     // filter for contract_address (2nd indexed topic)
     let topic2 = H256::from(chain_config.carbon_axelar_gateway.clone().parse::<Address>()?);
 
-    let event = ContractCallApprovedEvent::new::<_, Provider<Http>>(
-        Filter::new().address(address).topic2(topic2),
-        Arc::clone(&provider),
-    );
-    let mut events = event.stream().await?;
-
-    info!("Starting to watch {:?} {:?} for {:?} filtered by carbon contract: {:?}", &chain_config.chain_id, &chain_config.axelar_gateway_proxy, EVM_CONTRACT_CALL_APPROVED_EVENT, &chain_config.carbon_axelar_gateway);
-    while let Some(log) = events.next().await {
-        info!("found an event on {:?} {:?} for {:?} filtered by carbon contract: {:?}", &chain_config.chain_id, &chain_config.axelar_gateway_proxy, EVM_CONTRACT_CALL_APPROVED_EVENT, &chain_config.carbon_axelar_gateway);
-        match log {
-            Ok(event) => {
-                let chain_config = chain_config.clone();
-                let pg_pool = pg_pool.clone();
-                let _ = tokio::spawn(async move {
-                    info!("Received ContractCallApprovedEvent for carbon_axelar_gateway ({:?}): {:?}", &chain_config.carbon_axelar_gateway, event);
-                    save_call_contract_approved_event(chain_config.clone(), pg_pool.clone(), event).await;
-                });
-            }
-            Err(e) => error!("Error listening for ContractCallApprovedEvent logs: {:?}", e),
-        }
+    let filter = Filter::new().address(address).topic0(approved_event_topics()).topic2(topic2);
+    let mut logs = provider.watch(&filter).await?;
+
+    info!("Starting to watch {:?} {:?} for {:?} and {:?} filtered by carbon contract: {:?}", &chain_config.chain_id, &chain_config.axelar_gateway_proxy, EVM_CONTRACT_CALL_APPROVED_EVENT, EVM_CONTRACT_CALL_APPROVED_WITH_MINT_EVENT, &chain_config.carbon_axelar_gateway);
+    while let Some(log) = logs.next().await {
+        info!("found an event on {:?} {:?} filtered by carbon contract: {:?}", &chain_config.chain_id, &chain_config.axelar_gateway_proxy, &chain_config.carbon_axelar_gateway);
+        let chain_config = chain_config.clone();
+        let pg_pool = pg_pool.clone();
+        let _ = tokio::spawn(async move {
+            save_approved_log(chain_config, pg_pool, log).await;
+        });
     }
 
     Ok(())
@@ -166,30 +159,31 @@ async fn init_backfiller(chain_config: Chain, pg_pool: Arc<PgPool>) -> Result<()
     let poll_frequency = chain_config.backfill_poll_frequency.unwrap_or(300);
     info!("Connected to {:?} for backfilling every {:?}s", &chain_config.rpc_url, poll_frequency);
 
+    // the backfiller fetches the latest block on every poll, so it is stale once a couple of polls fail
+    let head_component = format!("evm_head:{}", &chain_config.chain_id);
+    health::register(&head_component, Some(Duration::from_secs(poll_frequency * 2 + 60)));
+
     let mut interval = tokio::time::interval(Duration::from_secs(poll_frequency));
 
     let chain_config_clone = chain_config.clone();
     let pool = pg_pool.clone();
     loop {
         debug!("Backfilling from {}", &chain_config_clone.rpc_url);
-        if let Err(e) = backfill(chain_config_clone.clone(), provider.clone(), pool.clone()).await {
-            error!("Failed to backfill from {}: {}", chain_config_clone.rpc_url, e);
+        match backfill(chain_config_clone.clone(), provider.clone(), pool.clone()).await {
+            Ok(_) => health::heartbeat(&head_component),
+            Err(e) => error!("Failed to backfill from {}: {}", chain_config_clone.rpc_url, e),
         }
         interval.tick().await;
     }
 }
 
-// backfill will attempt to search the last `max_query_blocks` blocks for any ContractCallApprovedEvent emitted by `axelar_gateway`
+// backfill will attempt to search the last `max_query_blocks` blocks for any ContractCallApproved or ContractCallApprovedWithMint event emitted by `axelar_gateway`
 // This is so that any missed events will be recorded
 async fn backfill(chain_config: Chain, provider: Arc<Provider<Http>>, pg_pool: Arc<PgPool>) -> Result<()> {
     // filter for axelar's gateway
     let address = chain_config.axelar_gateway_proxy.parse::<Address>()?;
     let address = ValueOrArray::Value(address);
 
-    // filter for event signature: ContractCallApprovedEvent
-    let event_signature = "ContractCallApproved(bytes32,string,string,address,bytes32,bytes32,uint256)";
-    let topic0 = H256::from(keccak256(event_signature.as_bytes()));  // Hash the event signature to get `topic0`
-
     // filter for contract_address (2nd indexed topic)
     let topic2 = H256::from(chain_config.carbon_axelar_gateway.clone().parse::<Address>()?);
 
@@ -203,7 +197,7 @@ async fn backfill(chain_config: Chain, provider: Arc<Provider<Http>>, pg_pool: A
     // Build the filter to query logs in the block range
     let filter = Filter::new()
         .address(address)
-        .topic0(topic0)
+        .topic0(approved_event_topics())
         .topic2(topic2)
         .from_block(from_block)
         .to_block(to_block);
@@ -213,14 +207,30 @@ async fn backfill(chain_config: Chain, provider: Arc<Provider<Http>>, pg_pool: A
 
     // Process each log
     for log in logs {
-        let chain_clone = chain_config.clone();
-        let pool_clone = pg_pool.clone();
-        if let Ok(event) = <ContractCallApprovedEvent as EthEvent>::decode_log(&RawLog::from(log)) {
-            save_call_contract_approved_event(chain_clone, pool_clone, event).await;
-        } else {
-            error!("Failed to decode log");
-        }
+        save_approved_log(chain_config.clone(), pg_pool.clone(), log).await;
     }
+
+    // everything up to to_block has been seen
+    save_chain_checkpoint(pg_pool.clone(), &chain_config.chain_id, to_block.as_u64() as i64).await?;
     Ok(())
 }
 
+// topic0 of the gateway events that are relayed
+pub fn approved_event_topics() -> Vec<H256> {
+    vec![ContractCallApprovedEvent::signature(), ContractCallApprovedWithMintEvent::signature()]
+}
+
+// decodes a ContractCallApproved or ContractCallApprovedWithMint log and saves it
+pub async fn save_approved_log(chain_config: Chain, pg_pool: Arc<PgPool>, log: Log) {
+    let raw_log = RawLog::from(log);
+    if let Ok(event) = <ContractCallApprovedEvent as EthEvent>::decode_log(&raw_log) {
+        info!("Received ContractCallApprovedEvent for carbon_axelar_gateway ({:?}): {:?}", &chain_config.carbon_axelar_gateway, event);
+        save_call_contract_approved_event(chain_config, pg_pool, event, None).await;
+    } else if let Ok(event) = <ContractCallApprovedWithMintEvent as EthEvent>::decode_log(&raw_log) {
+        info!("Received ContractCallApprovedWithMintEvent for carbon_axelar_gateway ({:?}): {:?}", &chain_config.carbon_axelar_gateway, event);
+        let (event, token) = event.split();
+        save_call_contract_approved_event(chain_config, pg_pool, event, Some(token)).await;
+    } else {
+        error!("Failed to decode log");
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use tracing::{info, warn};
+
+// hands out the nonces of a relayer account locally so that several executes can be in flight at once,
+// instead of reading the transaction count for every tx
+pub struct NonceManager {
+    address: Address,
+    state: Mutex<NonceState>,
+}
+
+struct NonceState {
+    next: U256,
+    // allocated to an execute that is still being sent
+    in_flight: BTreeSet<U256>,
+    // gaps found by the previous check, a nonce is only reported as a gap once it has been missing twice
+    // so that a tx still propagating to the node is not cancelled
+    suspected_gaps: BTreeSet<U256>,
+}
+
+// releases the nonce when dropped, an unused nonce at the top is handed out again
+pub struct NonceGuard<'a> {
+    manager: &'a NonceManager,
+    nonce: U256,
+    used: bool,
+}
+
+impl NonceGuard<'_> {
+    pub fn nonce(&self) -> U256 {
+        self.nonce
+    }
+
+    // a tx with this nonce has reached the node, so it can no longer be handed out again
+    pub fn mark_used(&mut self) {
+        self.used = true;
+    }
+}
+
+impl Drop for NonceGuard<'_> {
+    fn drop(&mut self) {
+        let mut state = self.manager.state.lock().unwrap();
+        state.in_flight.remove(&self.nonce);
+        if !self.used && self.nonce + 1 == state.next {
+            state.next = self.nonce;
+        }
+    }
+}
+
+impl NonceManager {
+    // starts from the pending transaction count, which includes txs that are still in the mempool
+    pub async fn new<M: Middleware>(provider: &M, address: Address) -> Result<Self>
+        where M::Error: 'static
+    {
+        let next = pending_count(provider, address).await?;
+        info!("Nonce manager for {:?} starting at nonce {}", address, next);
+        Ok(NonceManager {
+            address,
+            state: Mutex::new(NonceState { next, in_flight: BTreeSet::new(), suspected_gaps: BTreeSet::new() }),
+        })
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn next(&self) -> NonceGuard<'_> {
+        let mut state = self.state.lock().unwrap();
+        let nonce = state.next;
+        state.next += U256::one();
+        state.in_flight.insert(nonce);
+        NonceGuard { manager: self, nonce, used: false }
+    }
+
+    // catches up with txs sent by this account from elsewhere, e.g. after a "nonce too low" error
+    pub async fn sync<M: Middleware>(&self, provider: &M) -> Result<()>
+        where M::Error: 'static
+    {
+        let pending = pending_count(provider, self.address).await?;
+        let mut state = self.state.lock().unwrap();
+        if pending > state.next {
+            warn!("Nonce of {:?} moved from {} to {} outside of the nonce manager", self.address, state.next, pending);
+            state.next = pending;
+        }
+        Ok(())
+    }
+
+    // nonces that were handed out but are unknown to the node and are not being sent, every later tx is stuck behind them
+    pub async fn gaps<M: Middleware>(&self, provider: &M) -> Result<Vec<U256>>
+        where M::Error: 'static
+    {
+        let pending = pending_count(provider, self.address).await?;
+        let mut state = self.state.lock().unwrap();
+        let mut missing = BTreeSet::new();
+        let mut nonce = pending;
+        while nonce < state.next {
+            if !state.in_flight.contains(&nonce) {
+                missing.insert(nonce);
+            }
+            nonce += U256::one();
+        }
+        let gaps = missing.intersection(&state.suspected_gaps).copied().collect();
+        state.suspected_gaps = missing;
+        Ok(gaps)
+    }
+}
+
+async fn pending_count<M: Middleware>(provider: &M, address: Address) -> Result<U256>
+    where M::Error: 'static
+{
+    provider.get_transaction_count(address, Some(BlockNumber::Pending.into())).await
+        .context("Failed to get pending transaction count")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn manager(pending: u64) -> (NonceManager, Provider<MockProvider>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U256::from(pending)).unwrap();
+        let manager = NonceManager::new(&provider, Address::zero()).await.unwrap();
+        (manager, provider, mock)
+    }
+
+    #[tokio::test]
+    async fn hands_out_consecutive_nonces() {
+        let (manager, _, _) = manager(7).await;
+        let first = manager.next();
+        let second = manager.next();
+        assert_eq!(first.nonce(), U256::from(7));
+        assert_eq!(second.nonce(), U256::from(8));
+    }
+
+    #[tokio::test]
+    async fn unused_nonce_at_the_top_is_handed_out_again() {
+        let (manager, _, _) = manager(7).await;
+        drop(manager.next());
+        assert_eq!(manager.next().nonce(), U256::from(7));
+    }
+
+    #[tokio::test]
+    async fn used_nonce_is_not_handed_out_again() {
+        let (manager, _, _) = manager(7).await;
+        let mut nonce = manager.next();
+        nonce.mark_used();
+        drop(nonce);
+        assert_eq!(manager.next().nonce(), U256::from(8));
+    }
+
+    #[tokio::test]
+    async fn unused_nonce_below_the_top_is_not_rewound() {
+        let (manager, _, _) = manager(7).await;
+        let first = manager.next();
+        let mut second = manager.next();
+        second.mark_used();
+        drop(second);
+        drop(first);
+        assert_eq!(manager.next().nonce(), U256::from(9));
+    }
+
+    #[tokio::test]
+    async fn gap_is_reported_once_missing_twice() {
+        let (manager, provider, mock) = manager(7).await;
+        // nonce 7 was used but never reached the node, 8 is still being sent
+        let mut first = manager.next();
+        first.mark_used();
+        drop(first);
+        let _second = manager.next();
+
+        mock.push::<U256, _>(U256::from(7)).unwrap();
+        assert!(manager.gaps(&provider).await.unwrap().is_empty());
+        mock.push::<U256, _>(U256::from(7)).unwrap();
+        assert_eq!(manager.gaps(&provider).await.unwrap(), vec![U256::from(7)]);
+    }
+
+    #[tokio::test]
+    async fn mined_nonce_is_not_a_gap() {
+        let (manager, provider, mock) = manager(7).await;
+        let mut first = manager.next();
+        first.mark_used();
+        drop(first);
+
+        mock.push::<U256, _>(U256::from(7)).unwrap();
+        assert!(manager.gaps(&provider).await.unwrap().is_empty());
+        mock.push::<U256, _>(U256::from(8)).unwrap();
+        assert!(manager.gaps(&provider).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sync_catches_up_with_the_node() {
+        let (manager, provider, mock) = manager(7).await;
+        mock.push::<U256, _>(U256::from(12)).unwrap();
+        manager.sync(&provider).await.unwrap();
+        assert_eq!(manager.next().nonce(), U256::from(12));
+    }
+}
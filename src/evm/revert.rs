@@ -0,0 +1,155 @@
+use std::fmt;
+use std::sync::LazyLock;
+
+use ethers::abi::{decode, ParamType, Token};
+use ethers::utils::{hex, id};
+
+use crate::evm::broadcaster::IAXELAREXECUTABLE_ABI;
+
+// Error(string), used by require and revert with a message
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+// Panic(uint256), used by failed asserts, arithmetic errors and out of bounds accesses
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+// custom errors that the carbon gateway can revert with, from the abi the executes are sent with
+static GATEWAY_ERRORS: LazyLock<Vec<CustomError>> = LazyLock::new(|| {
+    IAXELAREXECUTABLE_ABI.errors.values()
+        .flatten()
+        .map(|error| CustomError::new(&error.name, error.inputs.iter().map(|input| input.kind.clone()).collect()))
+        .collect()
+});
+
+// errors that the execute will keep reverting with, anything else (including a revert without data) may pass later.
+// NotApprovedByGateway is also what an execute of an already executed call reverts with
+const PERMANENT_ERRORS: [&str; 2] = ["NotApprovedByGateway", "InvalidAddress"];
+
+struct CustomError {
+    name: String,
+    params: Vec<ParamType>,
+    selector: [u8; 4],
+}
+
+impl CustomError {
+    fn new(name: &str, params: Vec<ParamType>) -> Self {
+        let signature = format!("{}({})", name, params.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(","));
+        let selector = id(signature);
+        CustomError { name: name.to_string(), params, selector }
+    }
+}
+
+// whether the revert data is a known error that retrying the execute cannot fix
+pub fn is_permanent_revert(data: &[u8]) -> bool {
+    data.len() >= 4 && GATEWAY_ERRORS.iter()
+        .any(|error| error.selector == data[..4] && PERMANENT_ERRORS.contains(&error.name.as_str()))
+}
+
+// human readable reason of a revert from its return data
+pub fn decode_revert_reason(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "reverted without a reason".to_string()
+    }
+    if data.len() < 4 {
+        return format!("reverted with 0x{}", hex::encode(data))
+    }
+    let (selector, args) = data.split_at(4);
+
+    if selector == ERROR_SELECTOR {
+        if let Ok(tokens) = decode(&[ParamType::String], args) {
+            if let Some(Token::String(message)) = tokens.first() {
+                return format!("Error({})", message)
+            }
+        }
+    }
+    if selector == PANIC_SELECTOR {
+        if let Ok(tokens) = decode(&[ParamType::Uint(256)], args) {
+            if let Some(Token::Uint(code)) = tokens.first() {
+                return format!("Panic(0x{:02x}): {}", code, panic_description(code.low_u64()))
+            }
+        }
+    }
+    if let Some(error) = GATEWAY_ERRORS.iter().find(|e| e.selector == selector) {
+        if let Ok(tokens) = decode(&error.params, args) {
+            let args = tokens.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ");
+            return format!("{}({})", error.name, args)
+        }
+    }
+    format!("unknown custom error 0x{}", hex::encode(data))
+}
+
+fn panic_description(code: u64) -> &'static str {
+    match code {
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to an uninitialized function",
+        _ => "unknown panic code",
+    }
+}
+
+// returned when the pre-flight simulation of an execute reverts, the execute is then not broadcasted.
+// a permanent revert is not retried
+#[derive(Debug)]
+pub struct SimulationReverted {
+    pub reason: String,
+    pub permanent: bool,
+}
+
+impl fmt::Display for SimulationReverted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "execute simulation reverted: {}", self.reason)
+    }
+}
+
+impl std::error::Error for SimulationReverted {}
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::{encode, Address};
+    use ethers::types::U256;
+
+    use super::*;
+
+    fn with_selector(selector: [u8; 4], args: &[Token]) -> Vec<u8> {
+        [selector.to_vec(), encode(args)].concat()
+    }
+
+    #[test]
+    fn decodes_error_string() {
+        let data = with_selector(ERROR_SELECTOR, &[Token::String("not enough".to_string())]);
+        assert_eq!(decode_revert_reason(&data), "Error(not enough)");
+        assert!(!is_permanent_revert(&data));
+    }
+
+    #[test]
+    fn decodes_panic() {
+        let data = with_selector(PANIC_SELECTOR, &[Token::Uint(U256::from(0x11))]);
+        assert_eq!(decode_revert_reason(&data), "Panic(0x11): arithmetic overflow or underflow");
+    }
+
+    #[test]
+    fn decodes_gateway_errors() {
+        let data = id("NotApprovedByGateway()").to_vec();
+        assert_eq!(decode_revert_reason(&data), "NotApprovedByGateway()");
+        assert!(is_permanent_revert(&data));
+
+        let address = Address::repeat_byte(0x11);
+        let data = with_selector(id("SafeERC20FailedOperation(address)"), &[Token::Address(address)]);
+        assert_eq!(decode_revert_reason(&data), format!("SafeERC20FailedOperation({})", hex::encode(address)));
+        assert!(!is_permanent_revert(&data));
+    }
+
+    #[test]
+    fn empty_and_unknown_reverts_are_retryable() {
+        assert_eq!(decode_revert_reason(&[]), "reverted without a reason");
+        assert!(!is_permanent_revert(&[]));
+        assert_eq!(decode_revert_reason(&[0xab, 0xcd]), "reverted with 0xabcd");
+        let data = [0xde, 0xad, 0xbe, 0xef, 0x01];
+        assert_eq!(decode_revert_reason(&data), "unknown custom error 0xdeadbeef01");
+        assert!(!is_permanent_revert(&data));
+    }
+}
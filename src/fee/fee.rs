@@ -3,6 +3,8 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tracing::{debug};
 
+use crate::metrics::FEE_REJECTIONS;
+
 use crate::conf::{Fee, RelayStrategy};
 use crate::db::{DbPendingActionEvent};
 use crate::fee::strategies::{check_all_strategy, check_greater_than_0_strategy, check_hydrogen_strategy};
@@ -24,11 +26,15 @@ pub struct FeeResponse {
 pub async fn has_enough_fees(fee_config: &Fee, pending_action: DbPendingActionEvent) -> bool {
     let relay_details = pending_action.get_relay_details();
     debug!("relay_details from Carbon {:?}", relay_details);
-    match fee_config.relay_strategy {
+    let has_enough_fees = match fee_config.relay_strategy {
         RelayStrategy::Hydrogen => check_hydrogen_strategy(fee_config, &relay_details, &pending_action).await,
         RelayStrategy::All => check_all_strategy(),
         RelayStrategy::GreaterThan0 => check_greater_than_0_strategy(&relay_details),
+    };
+    if !has_enough_fees {
+        FEE_REJECTIONS.with_label_values(&[fee_config.relay_strategy.as_str()]).inc();
     }
+    has_enough_fees
 }
 
 pub async fn get_hydrogen_fee(fee_conf: &Fee, connection_id: &str, fee_denom: &str) -> Result<FeeResponse> {
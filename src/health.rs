@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::shutdown;
+
+// components reported by /readyz, keyed by name (e.g. `carbon_ws`, `evm_listener:bsc-testnet`)
+static COMPONENTS: LazyLock<Mutex<BTreeMap<String, Component>>> = LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+#[derive(Debug, Clone)]
+struct Component {
+    // a task that is not alive has exited and will not come back
+    task: bool,
+    alive: bool,
+    // a failed task that its supervisor is backing off to restart, it is not ready but still live
+    restarting: bool,
+    last_seen: Option<Instant>,
+    // a component without a max_age only needs to be alive
+    max_age: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentStatus {
+    pub ready: bool,
+    pub alive: bool,
+    pub restarting: bool,
+    pub last_seen_secs_ago: Option<u64>,
+}
+
+// marks a component as dead when dropped, so that a task that returns or panics is reported
+pub struct TaskGuard {
+    name: String,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if shutdown::is_requested() {
+            info!("Task {} has stopped", &self.name);
+        } else {
+            error!("Task {} has exited", &self.name);
+        }
+        set_alive(&self.name, false);
+    }
+}
+
+// registers a long-running task as alive until the returned guard is dropped
+pub fn task_started(name: &str) -> TaskGuard {
+    let mut components = COMPONENTS.lock().unwrap();
+    components.insert(name.to_string(), Component { task: true, alive: true, restarting: false, last_seen: None, max_age: None });
+    TaskGuard { name: name.to_string() }
+}
+
+// registers a component that is only ready after a heartbeat within max_age
+pub fn register(name: &str, max_age: Option<Duration>) {
+    let mut components = COMPONENTS.lock().unwrap();
+    components.insert(name.to_string(), Component { task: false, alive: true, restarting: false, last_seen: None, max_age });
+}
+
+// false once any task has exited and is not being restarted
+pub fn is_live() -> bool {
+    let components = COMPONENTS.lock().unwrap();
+    components.values().all(|component| !component.task || component.alive || component.restarting)
+}
+
+pub fn heartbeat(name: &str) {
+    let mut components = COMPONENTS.lock().unwrap();
+    if let Some(component) = components.get_mut(name) {
+        component.last_seen = Some(Instant::now());
+    }
+}
+
+pub fn set_alive(name: &str, alive: bool) {
+    let mut components = COMPONENTS.lock().unwrap();
+    if let Some(component) = components.get_mut(name) {
+        component.alive = alive;
+    }
+}
+
+// set by the supervisor while it backs off before restarting a task, and cleared when it gives up
+pub fn set_restarting(name: &str, restarting: bool) {
+    let mut components = COMPONENTS.lock().unwrap();
+    if let Some(component) = components.get_mut(name) {
+        component.restarting = restarting;
+    }
+}
+
+pub fn status() -> BTreeMap<String, ComponentStatus> {
+    let components = COMPONENTS.lock().unwrap();
+    components.iter()
+        .map(|(name, component)| {
+            let age = component.last_seen.map(|last_seen| last_seen.elapsed());
+            let fresh = match component.max_age {
+                Some(max_age) => age.map_or(false, |age| age <= max_age),
+                None => true,
+            };
+            let status = ComponentStatus {
+                ready: component.alive && fresh,
+                alive: component.alive,
+                restarting: component.restarting,
+                last_seen_secs_ago: age.map(|age| age.as_secs()),
+            };
+            (name.clone(), status)
+        })
+        .collect()
+}
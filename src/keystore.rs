@@ -0,0 +1,36 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::conf::Keystore;
+
+impl Keystore {
+    pub fn password(&self) -> Result<String> {
+        match (&self.password_env, &self.password_file) {
+            (Some(name), _) => std::env::var(name)
+                .with_context(|| format!("Failed to read keystore password from env {}", name)),
+            (None, Some(path)) => {
+                let password = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read keystore password from {}", path))?;
+                Ok(password.trim_end_matches(['\r', '\n']).to_string())
+            }
+            (None, None) => bail!("Keystore {} has no password_env or password_file", &self.path),
+        }
+    }
+
+    // the secret stored in the keystore, this is slow by design so it should only be done once
+    pub fn decrypt(&self) -> Result<Vec<u8>> {
+        eth_keystore::decrypt_key(&self.path, self.password()?)
+            .with_context(|| format!("Failed to decrypt keystore {}", &self.path))
+    }
+}
+
+// writes a secret into a web3 secret-storage keystore at path
+pub fn encrypt(path: &Path, secret: &[u8], password: &str) -> Result<()> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().and_then(|name| name.to_str()).context("Invalid keystore path")?;
+    eth_keystore::encrypt_key(dir, &mut ethers::core::rand::thread_rng(), secret, password, Some(name))
+        .with_context(|| format!("Failed to write keystore {}", path.display()))?;
+    Ok(())
+}
@@ -1,9 +1,11 @@
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use clap::{Parser, Subcommand};
 use sqlx::PgPool;
-use tracing::{info, Level};
+use tokio::task::JoinSet;
+use tracing::{error, Level};
 use tracing_subscriber::FmtSubscriber;
 
 use conf::AppConfig;
@@ -17,11 +19,20 @@ mod operational;
 mod carbon;
 mod evm;
 mod fee;
+mod api;
+mod metrics;
+mod health;
+mod supervisor;
+mod shutdown;
+mod balance;
+mod keystore;
+mod signer;
 
 mod switcheo {
     pub mod carbon {
         pub mod bridge {
             include!("../proto/gen/Switcheo.carbon.bridge.rs");
+            include!("../proto/gen/Switcheo.carbon.bridge.tonic.rs");
         }
     }
 }
@@ -50,6 +61,9 @@ enum Commands {
         /// Transaction hash to resync
         #[arg(value_name = "TX_HASH")]
         tx_hash: String,
+        /// Optional start block height for EVM sync
+        #[arg(short, long, value_name = "EVM_SYNC_FROM")]
+        evm_sync_from: Option<u64>,
     },
     /// Sync from a specific block range
     SyncFrom {
@@ -75,6 +89,26 @@ enum Commands {
         #[arg(value_name = "NONCES", num_args = 1.., value_delimiter=',')]
         nonces: Vec<u64>,
     },
+    /// Prints the bridge params and the axelar bridge connections with their external tokens, needs carbon.grpc_url
+    BridgeInfo,
+    /// Encrypts a carbon mnemonic read from stdin into a keystore for relayer_mnemonic_keystore
+    EncryptMnemonic {
+        /// path of the keystore file to write
+        #[arg(value_name = "PATH")]
+        path: PathBuf,
+        /// environment variable holding the keystore password
+        #[arg(long, value_name = "PASSWORD_ENV", default_value = "KEYSTORE_PASSWORD")]
+        password_env: String,
+    },
+    /// Runs a signing server for testing remote signers, keys are held in memory
+    MockSigner {
+        /// address to listen on
+        #[arg(long, value_name = "BIND_ADDRESS", default_value = "127.0.0.1:8600")]
+        bind_address: String,
+        /// key to serve as KEY_ID=KEY, where KEY is a hex private key or a mnemonic
+        #[arg(long = "key", value_name = "KEY_ID=KEY")]
+        keys: Vec<String>,
+    },
     /// Uses the provided tx_hash that contains a ContractCallApproved event and executes the tx
     ExecuteContractCallApproved {
         /// chain id should be the same as what is found on carbon's connection
@@ -122,6 +156,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::subscriber::set_global_default(subscriber)
         .expect("setting default subscriber failed");
 
+    // commands that need neither a config nor a database
+    if let Some(Commands::EncryptMnemonic { path, password_env }) = &cli.command {
+        operational::keystore::encrypt_mnemonic(path, password_env)?;
+        return Ok(())
+    }
+    if let Some(Commands::MockSigner { bind_address, keys }) = &cli.command {
+        signer::mock::serve(bind_address, keys).await?;
+        return Ok(())
+    }
+
     if !cli.config.exists() {
         eprintln!("⚠️ Config file not found at '{}'", cli.config.display());
         eprintln!("👉 Please copy the sample config from the project GitHub");
@@ -141,45 +185,108 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // initialize broadcaster_carbon
             let carbon_broadcaster = carbon::broadcaster::init_all(&conf.carbon).await;
 
-            // Spawn listener_carbon::init_ws as a concurrent task
+            let mut tasks = JoinSet::new();
+
+            // Spawn listener_carbon::init_ws as a supervised task
             let carbon_pg_pool = pg_pool.clone();
             let carbon_config = conf.carbon.clone();
             let fee_config = conf.fee.clone();
             let carbon_broadcaster_clone = carbon_broadcaster.clone();
-            let carbon_listen_task = tokio::spawn(async move {
-                carbon::listener::init_ws(&carbon_config, &fee_config, carbon_pg_pool, carbon_broadcaster_clone).await;
-            });
+            tasks.spawn(supervisor::supervise("carbon_listener".to_string(), conf.supervisor.clone(), move || {
+                let carbon_pg_pool = carbon_pg_pool.clone();
+                let carbon_config = carbon_config.clone();
+                let fee_config = fee_config.clone();
+                let carbon_broadcaster_clone = carbon_broadcaster_clone.clone();
+                async move {
+                    // no new pending actions are picked up once shutdown is requested
+                    tokio::select! {
+                        _ = carbon::listener::init_ws(&carbon_config, &fee_config, carbon_pg_pool, carbon_broadcaster_clone) => {}
+                        _ = shutdown::requested() => {}
+                    }
+                    Ok(())
+                }
+            }));
 
-            // Spawn retry_carbon::init_all as a concurrent task
+            // Spawn retry_carbon::init_all as a supervised task
             let carbon_pg_pool = pg_pool.clone();
             let carbon_config = conf.carbon.clone();
             let fee_config = conf.fee.clone();
             let carbon_broadcaster_clone = carbon_broadcaster.clone();
-            let carbon_retry_task = tokio::spawn(async move {
-                carbon::retry::init_all(&carbon_config, &fee_config, carbon_pg_pool, carbon_broadcaster_clone).await;
-            });
+            tasks.spawn(supervisor::supervise("carbon_retry".to_string(), conf.supervisor.clone(), move || {
+                let carbon_pg_pool = carbon_pg_pool.clone();
+                let carbon_config = carbon_config.clone();
+                let fee_config = fee_config.clone();
+                let carbon_broadcaster_clone = carbon_broadcaster_clone.clone();
+                async move {
+                    carbon::retry::init_all(&carbon_config, &fee_config, carbon_pg_pool, carbon_broadcaster_clone).await;
+                    Ok(())
+                }
+            }));
 
-            // Spawn listener_evm::init_all_ws as a concurrent task
+            // Spawn listener_evm::init_all, which supervises a listener and backfiller per chain
             let evm_pg_pool = pg_pool.clone();
             let evm_chains = conf.evm_chains.clone();
-            let evm_listen_all_task = tokio::spawn(async move {
-                evm::listener::init_all(evm_chains, evm_pg_pool).await;
+            let supervisor_conf = conf.supervisor.clone();
+            tasks.spawn(async move {
+                evm::listener::init_all(evm_chains, evm_pg_pool, supervisor_conf).await
             });
 
-            // Spawn broadcaster_evm::init_all as a concurrent task
+            // Spawn broadcaster_evm::init_all, which supervises a broadcaster per chain
             let broadcaster_evm_pg_pool = pg_pool.clone();
             let evm_chains = conf.evm_chains.clone();
-            let evm_execute_task = tokio::spawn(async move {
-                evm::broadcaster::init_all(evm_chains, broadcaster_evm_pg_pool).await;
+            let supervisor_conf = conf.supervisor.clone();
+            tasks.spawn(async move {
+                evm::broadcaster::init_all(evm_chains, broadcaster_evm_pg_pool, supervisor_conf).await
+            });
+
+            // Spawn api::server::init as a concurrent task if configured, failing to bind or serve exits the relayer
+            let api_pg_pool = pg_pool.clone();
+            let api_conf = conf.clone();
+            let carbon_broadcaster_clone = carbon_broadcaster.clone();
+            tasks.spawn(async move {
+                if let Some(api_config) = &api_conf.api {
+                    let state = api::server::ApiState { conf: api_conf.clone(), pg_pool: api_pg_pool, carbon_broadcaster: carbon_broadcaster_clone };
+                    api::server::init(api_config, state).await?;
+                }
+                Ok(())
             });
 
-            // Wait for all spawned tasks to complete
-            let _ = tokio::join!(carbon_listen_task, carbon_retry_task, evm_listen_all_task, evm_execute_task);
+            // Spawn metrics::init_all as a concurrent task
+            let metrics_pg_pool = pg_pool.clone();
+            let metrics_conf = conf.clone();
+            tasks.spawn(async move {
+                metrics::init_all(metrics_conf, metrics_pg_pool).await;
+                Ok(())
+            });
+
+            // Spawn balance::init_all as a supervised task, it pauses relays to chains whose relayer accounts run dry
+            let balance_conf = conf.clone();
+            tasks.spawn(supervisor::supervise("balance".to_string(), conf.supervisor.clone(), move || {
+                let balance_conf = balance_conf.clone();
+                async move {
+                    balance::init_all(balance_conf).await
+                }
+            }));
+
+            // Wait for all spawned tasks, exit non-zero once a supervised task has failed too many times.
+            // the tasks are kept until draining is done, so that queued broadcasts are still sent
+            let all_tasks = supervisor::wait_all(tasks);
+            tokio::pin!(all_tasks);
+            tokio::select! {
+                result = &mut all_tasks => {
+                    if let Err(e) = result {
+                        error!("Escalating task failure, exiting: {:?}", e);
+                        std::process::exit(1);
+                    }
+                }
+                _ = shutdown::signal() => {
+                    shutdown::drain(Duration::from_secs(conf.supervisor.shutdown_timeout_secs)).await;
+                }
+            }
         },
-        Some(Commands::Sync { tx_hash }) => {
+        Some(Commands::Sync { tx_hash, evm_sync_from }) => {
             // Call a function to handle the sync logic for a specific transaction hash
-            // TODO: implement
-            info!("NYI, input: {}", tx_hash);
+            operational::tx_sync::sync_tx(conf.clone(), pg_pool.clone(), tx_hash, *evm_sync_from).await?;
         },
         Some(Commands::SyncFrom { start_height, end_height, evm_sync_from }) => {
             // Call a function to handle the sync logic for a range of block heights
@@ -197,7 +304,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Call a function to handle the starting the relay
             let _ = operational::expire::expire_pending_actions(&conf.carbon.clone(), nonces.clone()).await;
         }
-        None => {}
+        Some(Commands::BridgeInfo) => {
+            operational::bridge_info::print_bridge_info(&conf.carbon).await?;
+        }
+        Some(Commands::EncryptMnemonic { .. }) | Some(Commands::MockSigner { .. }) | None => {}
     }
 
     Ok(())
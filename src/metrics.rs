@@ -0,0 +1,126 @@
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use ethers::prelude::*;
+use prometheus::{register_counter_vec, register_gauge_vec, register_int_counter_vec, register_int_gauge_vec, CounterVec, Encoder, GaugeVec, IntCounterVec, IntGaugeVec, TextEncoder};
+use sqlx::PgPool;
+use tokio::time::interval;
+use tracing::{debug, error, instrument};
+
+use crate::conf::AppConfig;
+use crate::db::carbon_events::count_pending_actions_by_status;
+use crate::db::checkpoints::get_chain_checkpoints;
+use crate::util::cosmos::get_latest_block_height;
+
+pub static PENDING_ACTIONS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!("relayer_pending_actions", "Number of pending actions by relay status", &["status"]).unwrap()
+});
+
+pub static START_RELAY_BROADCASTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("relayer_start_relay_broadcasts_total", "StartRelay broadcasts to carbon by outcome (attempted, succeeded, already_started, failed)", &["outcome"]).unwrap()
+});
+
+pub static EVM_EXECUTES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("relayer_evm_executes_total", "Execute txs broadcasted to evm chains by outcome (succeeded, failed, reverted)", &["chain", "outcome"]).unwrap()
+});
+
+pub static EVM_GAS_SPENT: LazyLock<CounterVec> = LazyLock::new(|| {
+    register_counter_vec!("relayer_evm_gas_spent_gwei_total", "Gas fees paid for execute txs in gwei", &["chain"]).unwrap()
+});
+
+pub static FEE_REJECTIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("relayer_fee_rejections_total", "Pending actions rejected for insufficient fees by relay strategy", &["strategy"]).unwrap()
+});
+
+pub static WS_RECONNECTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("relayer_ws_reconnects_total", "WebSocket reconnection attempts", &["url"]).unwrap()
+});
+
+pub static TASK_RESTARTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("relayer_task_restarts_total", "Restarts of supervised tasks after a failure", &["task"]).unwrap()
+});
+
+pub static LISTENER_LAG: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!("relayer_listener_lag_blocks", "Blocks between the chain head and the last block processed by the listener", &["chain"]).unwrap()
+});
+
+pub static WALLET_BALANCE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!("relayer_wallet_balance", "Native balance of the relayer accounts, in ether for evm chains and in fee_denom for carbon", &["chain", "address"]).unwrap()
+});
+
+pub static EXECUTOR_PAUSED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!("relayer_executor_paused", "1 when relays to the chain are paused as no relayer account can pay for an execute", &["chain"]).unwrap()
+});
+
+// renders all registered metrics in the prometheus text format
+pub fn gather() -> Result<String> {
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer).context("Failed to encode metrics")?;
+    String::from_utf8(buffer).context("Metrics are not valid utf8")
+}
+
+// records the fee paid by an execute tx from its receipt
+pub fn record_gas_spent(chain_id: &str, receipt: &TransactionReceipt) {
+    if let (Some(gas_used), Some(gas_price)) = (receipt.gas_used, receipt.effective_gas_price) {
+        let fee = gas_used.saturating_mul(gas_price);
+        let fee_gwei = ethers::utils::format_units(fee, "gwei").unwrap_or_default().parse::<f64>().unwrap_or_default();
+        EVM_GAS_SPENT.with_label_values(&[chain_id]).inc_by(fee_gwei);
+    }
+}
+
+// periodically samples the gauges that are derived from the db and the chains
+#[instrument(name = "metrics", skip_all)]
+pub async fn init_all(conf: AppConfig, pg_pool: Arc<PgPool>) {
+    let mut interval = interval(Duration::from_secs(30));
+    loop {
+        interval.tick().await;
+        if let Err(e) = update_pending_actions(pg_pool.clone()).await {
+            error!("Failed to update pending action metrics: {:?}", e);
+        }
+        if let Err(e) = update_listener_lag(&conf, pg_pool.clone()).await {
+            error!("Failed to update listener lag metrics: {:?}", e);
+        }
+    }
+}
+
+async fn update_pending_actions(pg_pool: Arc<PgPool>) -> Result<()> {
+    let counts = count_pending_actions_by_status(pg_pool).await?;
+    PENDING_ACTIONS.reset();
+    for (status, count) in counts {
+        PENDING_ACTIONS.with_label_values(&[&status]).set(count);
+    }
+    Ok(())
+}
+
+async fn update_listener_lag(conf: &AppConfig, pg_pool: Arc<PgPool>) -> Result<()> {
+    let checkpoints = get_chain_checkpoints(pg_pool).await?;
+    let last_processed_block = |chain_id: &str| checkpoints.iter()
+        .find(|c| c.chain_id == chain_id)
+        .map(|c| c.last_processed_block)
+        .unwrap_or_default();
+
+    match get_latest_block_height(&conf.carbon.rpc_url).await {
+        Ok(height) => {
+            let lag = (height as i64 - last_processed_block(&conf.carbon.chain_id)).max(0);
+            LISTENER_LAG.with_label_values(&[&conf.carbon.chain_id]).set(lag);
+        }
+        Err(e) => error!("Failed to get latest block height for {}: {:?}", &conf.carbon.chain_id, e),
+    }
+
+    for chain in &conf.evm_chains {
+        let height = match Provider::<Http>::try_from(chain.rpc_url.as_str()) {
+            Ok(provider) => provider.get_block_number().await.map_err(anyhow::Error::from),
+            Err(e) => Err(anyhow::Error::from(e)),
+        };
+        match height {
+            Ok(height) => {
+                let lag = (height.as_u64() as i64 - last_processed_block(&chain.chain_id)).max(0);
+                debug!("Listener lag for {} is {} blocks", &chain.chain_id, lag);
+                LISTENER_LAG.with_label_values(&[&chain.chain_id]).set(lag);
+            }
+            Err(e) => error!("Failed to get latest block number for {}: {:?}", &chain.chain_id, e),
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,6 @@
 pub mod expire;
 pub mod tx_sync;
 pub mod start_relay;
-pub mod execute_contract_call_approved;
\ No newline at end of file
+pub mod execute_contract_call_approved;
+pub mod keystore;
+pub mod bridge_info;
\ No newline at end of file
@@ -0,0 +1,24 @@
+use anyhow::{Context, Result};
+
+use crate::conf::Carbon;
+use crate::util::carbon::bridge::BridgeQueryClient;
+
+// prints the bridge params and the connections of the axelar bridge with their external tokens
+pub async fn print_bridge_info(carbon_config: &Carbon) -> Result<()> {
+    let grpc_url = carbon_config.grpc_url.as_ref()
+        .context("bridge-info needs carbon.grpc_url to be set")?;
+    let client = BridgeQueryClient::new(grpc_url)?;
+
+    let params = client.params().await?;
+    println!("{:#?}", params);
+
+    let bridge_id = carbon_config.axelar_bridge_id.parse::<u64>()
+        .context("Invalid axelar_bridge_id")?;
+    for connection in client.connections().await?.into_iter().filter(|c| c.bridge_id == bridge_id) {
+        println!("{:#?}", connection);
+        for mapping in client.external_token_mappings(&connection.connection_id).await? {
+            println!("  {} -> {} (enabled: {})", &mapping.external_address, &mapping.denom, mapping.is_enabled);
+        }
+    }
+    Ok(())
+}
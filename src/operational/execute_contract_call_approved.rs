@@ -14,6 +14,7 @@ use crate::conf::Chain;
 use crate::db::evm_events::update_broadcast_status;
 use crate::db::DbContractCallApprovedEvent;
 use crate::evm::broadcaster::{broadcast_tx, init_provider, IAxelarGateway};
+use crate::evm::nonce::NonceManager;
 use crate::util::evm::ContractCallApprovedEvent;
 
 // Utility function to check if a string is hex
@@ -43,6 +44,7 @@ pub async fn execute_contract_call_approved(
     );
 
     let provider = init_provider(chain_config.clone()).await?;
+    let nonces = NonceManager::new(provider.as_ref(), provider.address()).await?;
 
     let axelar_gateway = chain_config.axelar_gateway_proxy.parse::<Address>()?;
     let axelar_gateway = IAxelarGateway::new(axelar_gateway, provider.clone());
@@ -84,6 +86,13 @@ pub async fn execute_contract_call_approved(
                 source_tx_hash: hex::encode(decoded_log.source_tx_hash.as_bytes()),
                 source_event_index: BigDecimal::from(decoded_log.source_event_index.as_u64()),
                 payload: payload_hex.clone(), // Set payload appropriately
+                retry_count: 0,
+                execute_tx_hash: None,
+                gas_estimate: None,
+                revert_reason: None,
+                signer_address: None,
+                symbol: None,
+                amount: None,
             };
 
             let command_id =
@@ -113,7 +122,7 @@ pub async fn execute_contract_call_approved(
 
             // Call broadcast_tx function
             // broadcast_tx(chain_config.clone(), db_event, provider.clone()).await.context("Failed broadcast");
-            match broadcast_tx(chain_config.clone(), db_event, provider.clone()).await {
+            match broadcast_tx(chain_config.clone(), db_event, provider.clone(), &nonces, None).await {
                 Ok(_) => {
                     info!("broadcast successful");
                 }
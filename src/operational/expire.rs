@@ -17,8 +17,11 @@ pub async fn expire_pending_actions(carbon_config: &Carbon, nonces: Vec<u64>) {
     let response = send_msg_via_tx(carbon_config, msg_expire_actions).await;
 
     match response {
-        Ok(value) => {
-            info!("Received successful response: {:?}", value);
+        Ok(tx) if tx.is_success() => {
+            info!("tx {} included at height {}", &tx.tx_hash, tx.height);
+        }
+        Ok(tx) => {
+            eprintln!("tx {} failed with code {} ({}): {}", &tx.tx_hash, tx.code, &tx.codespace, &tx.raw_log);
         }
         Err(e) => {
             eprintln!("Failed to broadcast message: {:?}", e);
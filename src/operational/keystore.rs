@@ -0,0 +1,20 @@
+use std::io::BufRead;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use bip39::Mnemonic;
+
+use crate::keystore::encrypt;
+
+// reads a carbon mnemonic from stdin and encrypts it into a keystore for relayer_mnemonic_keystore
+pub fn encrypt_mnemonic(path: &Path, password_env: &str) -> Result<()> {
+    let password = std::env::var(password_env)
+        .with_context(|| format!("Failed to read keystore password from env {}", password_env))?;
+    eprintln!("Enter the mnemonic:");
+    let mut mnemonic = String::new();
+    std::io::stdin().lock().read_line(&mut mnemonic).context("Failed to read mnemonic")?;
+    let mnemonic = Mnemonic::parse(mnemonic.trim()).context("Invalid mnemonic")?;
+    encrypt(path, mnemonic.to_string().as_bytes(), &password)?;
+    eprintln!("Mnemonic keystore written to {}", path.display());
+    Ok(())
+}
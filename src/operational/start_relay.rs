@@ -17,8 +17,11 @@ pub async fn start_relay(carbon_config: &Carbon, nonce: u64) {
     let response = send_msg_via_tx(carbon_config, msg_start_relay).await;
 
     match response {
-        Ok(value) => {
-            info!("Received successful response: {:?}", value);
+        Ok(tx) if tx.is_success() => {
+            info!("tx {} included at height {}", &tx.tx_hash, tx.height);
+        }
+        Ok(tx) => {
+            eprintln!("tx {} failed with code {} ({}): {}", &tx.tx_hash, tx.code, &tx.codespace, &tx.raw_log);
         }
         Err(e) => {
             eprintln!("Failed to broadcast message: {:?}", e);
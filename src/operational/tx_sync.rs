@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use ethers::addressbook::Address;
-use ethers::prelude::{EthEvent, Filter, H256, Http, Middleware, Provider, ValueOrArray};
+use ethers::prelude::{Filter, H256, Http, Middleware, Provider, ValueOrArray};
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
@@ -10,12 +10,11 @@ use tracing::{error, info, instrument, warn};
 
 use crate::conf::{AppConfig, Chain};
 use crate::constants::events::{CARBON_AXELAR_CALL_CONTRACT_EVENT, CARBON_BRIDGE_PENDING_ACTION_EVENT, CARBON_UPDATE_PENDING_ACTION_EVENT};
-use crate::db::carbon_events::{get_chain_id_for_nonce, get_pending_action_by_nonce, save_axelar_call_contract_event, save_bridge_pending_action_event};
-use crate::db::DbAxelarCallContractEvent;
-use crate::db::evm_events::save_call_contract_approved_event;
+use crate::db::carbon_events::{get_chain_id_for_nonce, get_pending_action_by_nonce, save_axelar_call_contract_event, save_bridge_pending_action_event, transition_relay_status};
+use crate::db::{DbAxelarCallContractEvent, RelayStatus, StatusChange};
+use crate::evm::listener::{approved_event_topics, save_approved_log};
 use crate::util::carbon::parser::{parse_axelar_call_contract_event, parse_bridge_pending_action_event};
 use crate::util::cosmos::{Event, TxResultInner};
-use crate::util::evm::ContractCallApprovedEvent;
 
 #[derive(Serialize, Deserialize, Debug)]
 struct JsonRpcResult {
@@ -39,6 +38,13 @@ pub struct TxResult {
     pub tx: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct JsonRpcTxResult {
+    id: i32,
+    jsonrpc: String,
+    result: TxResult,
+}
+
 // 1) sync from carbon's start block height to end block height to find relevant txs
 // 2) loop through all event's payload_hash and sync evm txs based on the payload_hash found
 // 3) save to db, running relayer will continue and broadcast if needed
@@ -49,12 +55,49 @@ pub async fn sync_block_range(conf: AppConfig, pg_pool: Arc<PgPool>, start_heigh
     let query = format!("{}.connection_id CONTAINS '{}/' AND tx.height>={} AND tx.height<={}", CARBON_BRIDGE_PENDING_ACTION_EVENT, &conf.carbon.axelar_bridge_id, start_height, end_height);
     let response = abci_query(&conf.carbon.rpc_url, &query).await?;
     info!("Found {} transactions with {}", response.result.total_count, CARBON_BRIDGE_PENDING_ACTION_EVENT);
-    // extract all events and save events
-    for event in extract_events(response, CARBON_BRIDGE_PENDING_ACTION_EVENT) {
-        let bridge_pending_action_event = parse_bridge_pending_action_event(event.clone());
+    save_pending_action_events(&conf, pg_pool.clone(), extract_events(&response.result.txs, CARBON_BRIDGE_PENDING_ACTION_EVENT)).await;
+
+    // Find and save CARBON_AXELAR_CALL_CONTRACT_EVENT event
+    let query = format!("{}.nonce EXISTS AND tx.height>={} AND tx.height<={}", CARBON_AXELAR_CALL_CONTRACT_EVENT, start_height, end_height);
+    let response = abci_query(&conf.carbon.rpc_url, &query).await?;
+    info!("Found {} transactions with {}", response.result.total_count, CARBON_AXELAR_CALL_CONTRACT_EVENT);
+    let call_contract_events = save_call_contract_events(pg_pool.clone(), extract_events(&response.result.txs, CARBON_AXELAR_CALL_CONTRACT_EVENT)).await;
+
+    // Find and save EVM event for each new payload_hash found
+    sync_contract_call_approved_events(&conf, pg_pool.clone(), call_contract_events, evm_sync_from).await
+}
+
+// 1) fetch a single carbon tx by its hash
+// 2) extract and save the same events as sync_block_range
+// 3) look up the matching ContractCallApproved event on the destination chain so the running relayer can broadcast it
+pub async fn sync_tx(conf: AppConfig, pg_pool: Arc<PgPool>, tx_hash: &str, evm_sync_from: Option<u64>) -> Result<()> {
+    info!("Syncing {:?} for tx {}", &conf.carbon.rpc_url, tx_hash);
+
+    let tx = get_tx(&conf.carbon.rpc_url, tx_hash).await?;
+    info!("Found tx {} at height {}", &tx.hash, &tx.height);
+    let txs = vec![tx];
+
+    // Find and save CARBON_BRIDGE_PENDING_ACTION_EVENT event
+    save_pending_action_events(&conf, pg_pool.clone(), extract_events(&txs, CARBON_BRIDGE_PENDING_ACTION_EVENT)).await;
 
+    // Find and save CARBON_AXELAR_CALL_CONTRACT_EVENT event
+    let call_contract_events = save_call_contract_events(pg_pool.clone(), extract_events(&txs, CARBON_AXELAR_CALL_CONTRACT_EVENT)).await;
+
+    // Find and save EVM event for each new payload_hash found
+    sync_contract_call_approved_events(&conf, pg_pool.clone(), call_contract_events, evm_sync_from).await
+}
 
-        if is_broadcasted_by_relayer(&conf, bridge_pending_action_event.nonce).await {
+// parses and saves CARBON_BRIDGE_PENDING_ACTION_EVENT events that belong to the axelar bridge
+async fn save_pending_action_events(conf: &AppConfig, pg_pool: Arc<PgPool>, events: Vec<Event>) {
+    let connection_prefix = format!("{}/", &conf.carbon.axelar_bridge_id);
+    for event in events {
+        let bridge_pending_action_event = parse_bridge_pending_action_event(event.clone());
+        if !bridge_pending_action_event.connection_id.starts_with(&connection_prefix) {
+            info!("Skipping event with nonce {:?} as connection {:?} is not for bridge {:?}", bridge_pending_action_event.nonce, &bridge_pending_action_event.connection_id, &conf.carbon.axelar_bridge_id);
+            continue
+        }
+
+        if is_broadcasted_by_relayer(conf, bridge_pending_action_event.nonce).await {
             info!("saving pending action that was relayed by this relayer");
         } else {
             // check if relay has expired
@@ -65,17 +108,14 @@ pub async fn sync_block_range(conf: AppConfig, pg_pool: Arc<PgPool>, start_heigh
             }
         }
 
-
         save_bridge_pending_action_event(pg_pool.clone(), &bridge_pending_action_event).await;
     }
+}
 
+// parses and saves CARBON_AXELAR_CALL_CONTRACT_EVENT events, returns all parsed events so that their evm events can be synced
+async fn save_call_contract_events(pg_pool: Arc<PgPool>, events: Vec<Event>) -> Vec<DbAxelarCallContractEvent> {
     let mut call_contract_events: Vec<DbAxelarCallContractEvent> = Vec::new();
-    // Find and save CARBON_AXELAR_CALL_CONTRACT_EVENT event
-    let query = format!("{}.nonce EXISTS AND tx.height>={} AND tx.height<={}", CARBON_AXELAR_CALL_CONTRACT_EVENT, start_height, end_height);
-    let response = abci_query(&conf.carbon.rpc_url, &query).await?;
-    info!("Found {} transactions with {}", response.result.total_count, CARBON_AXELAR_CALL_CONTRACT_EVENT);
-    // extract all events and save events
-    for event in extract_events(response, CARBON_AXELAR_CALL_CONTRACT_EVENT) {
+    for event in events {
         let axelar_call_contract_event = parse_axelar_call_contract_event(event);
         call_contract_events.push(axelar_call_contract_event.clone());
         if !should_save_call_contract_event(pg_pool.clone(), &axelar_call_contract_event).await {
@@ -83,13 +123,19 @@ pub async fn sync_block_range(conf: AppConfig, pg_pool: Arc<PgPool>, start_heigh
             continue
         }
         save_axelar_call_contract_event(pg_pool.clone(), &axelar_call_contract_event.clone()).await;
+        if let Err(e) = transition_relay_status(pg_pool.clone(), axelar_call_contract_event.nonce, RelayStatus::CallContractSeen, &StatusChange::reason("axelar call contract synced from carbon")).await {
+            error!("Failed to transition relay status for nonce {:?}, err: {:?}", axelar_call_contract_event.nonce, e);
+        }
     }
+    call_contract_events
+}
 
-    // Find and save EVM event for each new payload_hash found
+// finds and saves the ContractCallApproved event on the destination chain for each call_contract event
+async fn sync_contract_call_approved_events(conf: &AppConfig, pg_pool: Arc<PgPool>, call_contract_events: Vec<DbAxelarCallContractEvent>, evm_sync_from: Option<u64>) -> Result<()> {
     for event in call_contract_events {
         // check if event was broadcasted by relayer
 
-        if !is_broadcasted_by_relayer(&conf, event.nonce).await {
+        if !is_broadcasted_by_relayer(conf, event.nonce).await {
             info!("there's a pending action that was relayed but not by this relayer, so skip saving AxelarCallContract");
             continue
         }
@@ -115,8 +161,13 @@ pub async fn sync_block_range(conf: AppConfig, pg_pool: Arc<PgPool>, start_heigh
             }
         };
 
-        let chain_config = conf.evm_chains.iter().find(|a| a.chain_id == chain_id).unwrap();
-        let chain_config = chain_config.clone();
+        let chain_config = match conf.evm_chains.iter().find(|a| a.chain_id == chain_id) {
+            Some(chain_config) => chain_config.clone(),
+            None => {
+                warn!("Skipping as chain {:?} for nonce {:?} is not configured", &chain_id, event.nonce);
+                continue
+            }
+        };
         // save corresponding evm event
         save_contract_call_approved_events(chain_config, pg_pool.clone(), &event.payload_hash, evm_sync_from).await.context("save contract call approved event failed")?;
     }
@@ -147,8 +198,8 @@ async fn should_save_call_contract_event(pg_pool: Arc<PgPool>, axelar_call_contr
     }
 }
 
-fn extract_events(response: JsonRpcResult, event_type: &str) -> Vec<Event> {
-    response.result.txs.iter()
+fn extract_events(txs: &[TxResult], event_type: &str) -> Vec<Event> {
+    txs.iter()
         .flat_map(|tx| {
             let events: Vec<Event> = tx.tx_result.events.iter().filter(|e| e.event_type == event_type).cloned().collect();
             events
@@ -173,6 +224,21 @@ async fn abci_query(carbon_rpc_url: &str, query: &str) -> Result<JsonRpcResult>
         .await.context("json deserializing failed")
 }
 
+// fetches a single tx from carbon's TM RPC by its hash
+async fn get_tx(carbon_rpc_url: &str, tx_hash: &str) -> Result<TxResult> {
+    // TM RPC expects the hash to be 0x prefixed
+    let tx_hash = tx_hash.trim_start_matches("0x");
+    let query_url = format!("{}/tx?hash=0x{}", carbon_rpc_url, tx_hash);
+
+    let client = reqwest::Client::new();
+    let response = client.get(&query_url)
+        .send()
+        .await.context("tx request send failed")?
+        .json::<JsonRpcTxResult>()
+        .await.context("json deserializing failed, tx may not exist")?;
+    Ok(response.result)
+}
+
 #[instrument(name = "tx_sync::save_contract_call_approved_events", skip_all, fields(chain = chain_config.chain_id))]
 async fn save_contract_call_approved_events(chain_config: Chain, pg_pool: Arc<PgPool>, payload_hash: &str, override_from_block: Option<u64>) -> Result<()> {
     let provider = Provider::<Http>::try_from(&chain_config.rpc_url)?;
@@ -195,21 +261,19 @@ async fn save_contract_call_approved_events(chain_config: Chain, pg_pool: Arc<Pg
     // Use override value if it exists
     let from_block = if override_from_block.is_some() { override_from_block.unwrap() } else { from_block };
 
-    let event = ContractCallApprovedEvent::new::<_, Provider<Http>>(
-        Filter::new().address(address)
-            .topic2(topic2)
-            .topic3(topic3)
-            .from_block(from_block),
-        Arc::clone(&provider)
-    );
-    let events = event.query().await?;
-    info!("{} events found!", events.iter().len());
+    let filter = Filter::new().address(address)
+        .topic0(approved_event_topics())
+        .topic2(topic2)
+        .topic3(topic3)
+        .from_block(from_block);
+    let logs = provider.get_logs(&filter).await?;
+    info!("{} events found!", logs.len());
 
     // loop all events found
-    for event in events {
+    for log in logs {
         let chain_config = chain_config.clone();
         let pg_pool = pg_pool.clone();
-        save_call_contract_approved_event(chain_config, pg_pool, event).await;
+        save_approved_log(chain_config, pg_pool, log).await;
     }
 
     Ok(())
@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use tokio::sync::{watch, Notify};
+use tokio::time::timeout;
+use tracing::{info, warn};
+
+static SHUTDOWN: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::channel(false).0);
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static DRAINED: Notify = Notify::const_new();
+
+// held while a carbon or evm broadcast is in flight so that shutdown waits for it
+pub struct InFlightGuard;
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if IN_FLIGHT.fetch_sub(1, Ordering::SeqCst) == 1 {
+            DRAINED.notify_waiters();
+        }
+    }
+}
+
+pub fn in_flight() -> InFlightGuard {
+    IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    InFlightGuard
+}
+
+pub fn is_requested() -> bool {
+    *SHUTDOWN.borrow()
+}
+
+// resolves once shutdown has been requested, used to stop loops from picking up new work
+pub async fn requested() {
+    let mut rx = SHUTDOWN.subscribe();
+    let _ = rx.wait_for(|requested| *requested).await;
+}
+
+// resolves on SIGINT or SIGTERM
+pub async fn signal() {
+    let ctrl_c = tokio::signal::ctrl_c();
+    #[cfg(unix)]
+    {
+        let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler");
+        tokio::select! {
+            _ = ctrl_c => {},
+            _ = sigterm.recv() => {},
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = ctrl_c.await;
+}
+
+// asks all loops to stop taking new work and waits for in-flight broadcasts, returns false on timeout
+pub async fn drain(drain_timeout: Duration) -> bool {
+    SHUTDOWN.send_replace(true);
+    info!("Shutdown requested, waiting up to {:?} for {} in-flight broadcasts", drain_timeout, IN_FLIGHT.load(Ordering::SeqCst));
+    let drained = timeout(drain_timeout, async {
+        loop {
+            let notified = DRAINED.notified();
+            if IN_FLIGHT.load(Ordering::SeqCst) == 0 {
+                return
+            }
+            notified.await;
+        }
+    }).await.is_ok();
+    if !drained {
+        warn!("{} broadcasts still in flight after {:?}, they will be reconciled on the next startup", IN_FLIGHT.load(Ordering::SeqCst), drain_timeout);
+    }
+    drained
+}
@@ -0,0 +1,48 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use k256::ecdsa::SigningKey;
+
+pub mod evm;
+pub mod mock;
+pub mod remote;
+
+// an ecdsa signature over a 32 byte digest
+#[derive(Clone, Debug)]
+pub struct DigestSignature {
+    // 64 bytes of r || s, with a low s
+    pub signature: Vec<u8>,
+    // recovery id of the public key (0 or 1), needed for evm signatures
+    pub recovery_id: u8,
+}
+
+// signs secp256k1 digests for carbon and evm txs, either with a local key or through a remote signing service
+#[async_trait]
+pub trait DigestSigner: Send + Sync {
+    // 33 byte compressed public key
+    fn public_key(&self) -> Vec<u8>;
+
+    async fn sign_digest(&self, digest: [u8; 32]) -> Result<DigestSignature>;
+}
+
+pub struct LocalSigner {
+    key: SigningKey,
+}
+
+impl LocalSigner {
+    pub fn new(key: SigningKey) -> Self {
+        LocalSigner { key }
+    }
+}
+
+#[async_trait]
+impl DigestSigner for LocalSigner {
+    fn public_key(&self) -> Vec<u8> {
+        self.key.verifying_key().to_encoded_point(true).as_bytes().to_vec()
+    }
+
+    async fn sign_digest(&self, digest: [u8; 32]) -> Result<DigestSignature> {
+        let (signature, recovery_id) = self.key.sign_prehash_recoverable(&digest)
+            .context("Failed to sign digest")?;
+        Ok(DigestSignature { signature: signature.to_bytes().to_vec(), recovery_id: recovery_id.to_byte() })
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use ethers::core::k256::ecdsa::VerifyingKey;
+use ethers::prelude::*;
+use ethers::signers::LocalWallet;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::utils::{hash_message, public_key_to_address};
+
+use crate::conf::Chain;
+use crate::signer::DigestSigner;
+use crate::signer::remote::RemoteSigner;
+
+// provider used to send executes, signing with a relayer key
+pub type EvmProvider = SignerMiddleware<Provider<Http>, EvmSigner>;
+
+// a relayer key of an evm chain, either held locally or by a remote signing service
+#[derive(Clone, Debug)]
+pub enum EvmSigner {
+    Local(LocalWallet),
+    Remote { signer: Arc<RemoteSigner>, address: Address, chain_id: u64 },
+}
+
+#[derive(Debug)]
+pub struct EvmSignerError(String);
+
+impl fmt::Display for EvmSignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for EvmSignerError {}
+
+impl EvmSigner {
+    pub fn remote(signer: RemoteSigner) -> Result<Self> {
+        let public_key = VerifyingKey::from_sec1_bytes(&signer.public_key())
+            .context("Remote signer returned an invalid public key")?;
+        let address = public_key_to_address(&public_key);
+        Ok(EvmSigner::Remote { signer: Arc::new(signer), address, chain_id: 1 })
+    }
+
+    async fn sign_hash_remote(signer: &RemoteSigner, hash: H256) -> Result<Signature, EvmSignerError> {
+        let signature = signer.sign_digest(hash.0).await.map_err(|e| EvmSignerError(format!("{:?}", e)))?;
+        Ok(Signature {
+            r: U256::from_big_endian(&signature.signature[..32]),
+            s: U256::from_big_endian(&signature.signature[32..]),
+            v: signature.recovery_id as u64 + 27,
+        })
+    }
+}
+
+#[async_trait]
+impl Signer for EvmSigner {
+    type Error = EvmSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        match self {
+            EvmSigner::Local(wallet) => wallet.sign_message(message).await.map_err(|e| EvmSignerError(e.to_string())),
+            EvmSigner::Remote { signer, .. } => EvmSigner::sign_hash_remote(signer, hash_message(message)).await,
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            EvmSigner::Local(wallet) => wallet.sign_transaction(tx).await.map_err(|e| EvmSignerError(e.to_string())),
+            EvmSigner::Remote { signer, chain_id, .. } => {
+                let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(*chain_id);
+                signer.sign_tx(tx, chain_id).await.map_err(|e| EvmSignerError(format!("{:?}", e)))
+            }
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature, Self::Error> {
+        match self {
+            EvmSigner::Local(wallet) => wallet.sign_typed_data(payload).await.map_err(|e| EvmSignerError(e.to_string())),
+            EvmSigner::Remote { signer, .. } => {
+                let hash = payload.encode_eip712().map_err(|e| EvmSignerError(e.to_string()))?;
+                EvmSigner::sign_hash_remote(signer, H256(hash)).await
+            }
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            EvmSigner::Local(wallet) => wallet.address(),
+            EvmSigner::Remote { address, .. } => *address,
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            EvmSigner::Local(wallet) => wallet.chain_id(),
+            EvmSigner::Remote { chain_id, .. } => *chain_id,
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            EvmSigner::Local(wallet) => EvmSigner::Local(wallet.with_chain_id(chain_id)),
+            EvmSigner::Remote { signer, address, .. } => EvmSigner::Remote { signer, address, chain_id: chain_id.into() },
+        }
+    }
+}
+
+// the relayer keys of the chain, from plaintext keys followed by keystores and remote signers
+pub async fn load_signers(chain: &Chain) -> Result<Vec<EvmSigner>> {
+    // keystores are slow to decrypt by design
+    let wallets = {
+        let chain = chain.clone();
+        tokio::task::spawn_blocking(move || load_wallets(&chain)).await??
+    };
+    let mut signers: Vec<EvmSigner> = wallets.into_iter().map(EvmSigner::Local).collect();
+    for remote in &chain.remote_signers {
+        let signer = RemoteSigner::connect(remote).await?;
+        signers.push(EvmSigner::remote(signer)?);
+    }
+    if signers.is_empty() {
+        anyhow::bail!("No relayer_private_key, relayer_keystores or remote_signers configured for {}", &chain.chain_id);
+    }
+    Ok(signers)
+}
+
+fn load_wallets(chain: &Chain) -> Result<Vec<LocalWallet>> {
+    let mut wallets = Vec::new();
+    for key in chain.signer_keys() {
+        wallets.push(key.parse::<LocalWallet>().context("Error parsing wallet key")?);
+    }
+    for keystore in &chain.relayer_keystores {
+        let key = keystore.decrypt()?;
+        wallets.push(LocalWallet::from_bytes(&key).context("Error parsing wallet key")?);
+    }
+    Ok(wallets)
+}
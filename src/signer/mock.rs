@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::utils::hex;
+use k256::ecdsa::SigningKey;
+use tracing::info;
+
+use crate::signer::remote::{PublicKeyResponse, SignDigestRequest, SignDigestResponse, SignTxRequest, SignTxResponse};
+use crate::signer::{DigestSigner, LocalSigner};
+use crate::util::carbon::tx::derive_key;
+
+type Keys = Arc<HashMap<String, SigningKey>>;
+
+// errors are returned as plain text, which the remote signer includes in its error
+enum MockError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for MockError {
+    fn from(e: anyhow::Error) -> Self {
+        MockError::Internal(e)
+    }
+}
+
+impl IntoResponse for MockError {
+    fn into_response(self) -> Response {
+        match self {
+            MockError::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            MockError::NotFound(message) => (StatusCode::NOT_FOUND, message).into_response(),
+            MockError::Internal(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:?}", e)).into_response(),
+        }
+    }
+}
+
+// a signing server holding keys in memory, for testing the remote signer locally. it does not check auth tokens
+pub async fn serve(bind_address: &str, keys: &[String]) -> Result<()> {
+    let addr = bind_address.parse::<SocketAddr>()
+        .with_context(|| format!("Invalid bind address {:?}", bind_address))?;
+    let keys: Keys = Arc::new(keys.iter().map(|key| parse_key(key)).collect::<Result<_>>()?);
+    info!("Starting mock signer on {:?} with keys {:?}", addr, keys.keys().collect::<Vec<_>>());
+
+    axum::Server::try_bind(&addr)
+        .with_context(|| format!("Failed to bind mock signer to {:?}", addr))?
+        .serve(router(keys).into_make_service())
+        .await
+        .context("Mock signer failed")
+}
+
+fn router(keys: Keys) -> Router {
+    Router::new()
+        .route("/keys/:key_id", get(get_public_key))
+        .route("/keys/:key_id/sign_digest", post(sign_digest))
+        .route("/keys/:key_id/sign_tx", post(sign_tx))
+        .with_state(keys)
+}
+
+// KEY_ID=KEY, where the key is a hex private key or a mnemonic that is derived with the cosmos hd path
+fn parse_key(arg: &str) -> Result<(String, SigningKey)> {
+    let (key_id, key) = arg.split_once('=').ok_or_else(|| anyhow!("Expected KEY_ID=KEY, got {:?}", arg))?;
+    let key = if key.trim().contains(' ') {
+        derive_key(key.trim())?
+    } else {
+        let bytes = hex::decode(key.trim()).with_context(|| format!("Invalid private key for {}", key_id))?;
+        SigningKey::from_slice(&bytes).with_context(|| format!("Invalid private key for {}", key_id))?
+    };
+    Ok((key_id.to_string(), key))
+}
+
+fn find_key(keys: &Keys, key_id: &str) -> Result<SigningKey, MockError> {
+    keys.get(key_id).cloned().ok_or_else(|| MockError::NotFound(format!("unknown key {}", key_id)))
+}
+
+async fn get_public_key(State(keys): State<Keys>, Path(key_id): Path<String>) -> Result<Json<PublicKeyResponse>, MockError> {
+    let signer = LocalSigner::new(find_key(&keys, &key_id)?);
+    Ok(Json(PublicKeyResponse { public_key: hex::encode_prefixed(signer.public_key()) }))
+}
+
+async fn sign_digest(State(keys): State<Keys>, Path(key_id): Path<String>, Json(request): Json<SignDigestRequest>) -> Result<Json<SignDigestResponse>, MockError> {
+    let signer = LocalSigner::new(find_key(&keys, &key_id)?);
+    let digest: [u8; 32] = hex::decode(&request.digest).ok()
+        .and_then(|digest| digest.try_into().ok())
+        .ok_or_else(|| MockError::BadRequest("digest must be 32 bytes of hex".to_string()))?;
+    let signature = signer.sign_digest(digest).await?;
+    Ok(Json(SignDigestResponse { signature: hex::encode_prefixed(signature.signature), recovery_id: signature.recovery_id }))
+}
+
+async fn sign_tx(State(keys): State<Keys>, Path(key_id): Path<String>, Json(request): Json<SignTxRequest>) -> Result<Json<SignTxResponse>, MockError> {
+    let wallet = LocalWallet::from(find_key(&keys, &key_id)?).with_chain_id(request.chain_id);
+    let signature = wallet.sign_transaction_sync(&request.tx).context("Failed to sign tx")?;
+    info!("Signed tx for {} from {:?} on chain {}", &key_id, wallet.address(), request.chain_id);
+    Ok(Json(SignTxResponse { signature }))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::{Address, TransactionRequest, U256};
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+    use crate::conf::RemoteKey;
+    use crate::signer::remote::RemoteSigner;
+
+    use super::*;
+
+    const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    // serves the mock signer on a free local port
+    fn start(keys: &[String]) -> String {
+        let keys: Keys = Arc::new(keys.iter().map(|key| parse_key(key)).collect::<Result<_>>().unwrap());
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = axum::Server::from_tcp(listener).unwrap().serve(router(keys).into_make_service());
+        tokio::spawn(server);
+        url
+    }
+
+    fn remote_key(url: &str, key_id: &str) -> RemoteKey {
+        RemoteKey { url: url.to_string(), key_id: key_id.to_string(), auth_token_env: None }
+    }
+
+    #[tokio::test]
+    async fn signs_digest_through_remote_signer() {
+        let url = start(&[format!("relayer={}", KEY)]);
+        let signer = RemoteSigner::connect(&remote_key(&url, "relayer")).await.unwrap();
+        let key = SigningKey::from_slice(&hex::decode(KEY).unwrap()).unwrap();
+        assert_eq!(signer.public_key(), LocalSigner::new(key.clone()).public_key());
+
+        let digest = [7u8; 32];
+        let signature = signer.sign_digest(digest).await.unwrap();
+        let recovered = VerifyingKey::recover_from_prehash(
+            &digest,
+            &Signature::from_slice(&signature.signature).unwrap(),
+            RecoveryId::from_byte(signature.recovery_id).unwrap(),
+        ).unwrap();
+        assert_eq!(&recovered, key.verifying_key());
+    }
+
+    #[tokio::test]
+    async fn signs_tx_through_remote_signer() {
+        let url = start(&[format!("relayer={}", KEY)]);
+        let signer = RemoteSigner::connect(&remote_key(&url, "relayer")).await.unwrap();
+        let wallet = LocalWallet::from(SigningKey::from_slice(&hex::decode(KEY).unwrap()).unwrap()).with_chain_id(5u64);
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .from(wallet.address())
+            .to(Address::repeat_byte(1))
+            .value(U256::from(100))
+            .nonce(3)
+            .gas(21000)
+            .gas_price(U256::from(1_000_000_000u64))
+            .chain_id(5u64)
+            .into();
+        let signature = signer.sign_tx(&tx, 5).await.unwrap();
+        assert_eq!(signature.recover(tx.sighash()).unwrap(), wallet.address());
+    }
+
+    #[tokio::test]
+    async fn unknown_key_fails_to_connect() {
+        let url = start(&[format!("relayer={}", KEY)]);
+        let err = RemoteSigner::connect(&remote_key(&url, "other")).await.unwrap_err();
+        assert!(format!("{:?}", err).contains("404"));
+    }
+}
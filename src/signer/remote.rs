@@ -0,0 +1,122 @@
+use std::fmt;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::Signature;
+use ethers::utils::hex;
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::conf::RemoteKey;
+use crate::signer::{DigestSignature, DigestSigner};
+
+// the signing protocol, keys are addressed by id:
+// GET  {url}/keys/{key_id}             -> PublicKeyResponse
+// POST {url}/keys/{key_id}/sign_digest -> SignDigestRequest, SignDigestResponse
+// POST {url}/keys/{key_id}/sign_tx     -> SignTxRequest, SignTxResponse
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicKeyResponse {
+    pub public_key: String, // hex of the 33 byte compressed public key
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignDigestRequest {
+    pub digest: String, // hex of the 32 byte digest
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignDigestResponse {
+    pub signature: String, // hex of the 64 byte r || s, with a low s
+    pub recovery_id: u8,
+}
+
+// evm txs are sent whole so that the signing service can check what it signs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignTxRequest {
+    pub chain_id: u64,
+    pub tx: TypedTransaction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignTxResponse {
+    pub signature: Signature,
+}
+
+// a key held by a remote signing service
+pub struct RemoteSigner {
+    client: Client,
+    url: String,
+    key_id: String,
+    auth_token: Option<String>,
+    public_key: Vec<u8>,
+}
+
+impl fmt::Debug for RemoteSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteSigner").field("url", &self.url).field("key_id", &self.key_id).finish()
+    }
+}
+
+impl RemoteSigner {
+    // fetches the public key of the key so that the signer can be used without a round trip for it
+    pub async fn connect(conf: &RemoteKey) -> Result<Self> {
+        let auth_token = match &conf.auth_token_env {
+            Some(name) => Some(std::env::var(name).with_context(|| format!("Failed to read remote signer token from env {}", name))?),
+            None => None,
+        };
+        let mut signer = RemoteSigner {
+            client: Client::new(),
+            url: conf.url.trim_end_matches('/').to_string(),
+            key_id: conf.key_id.clone(),
+            auth_token,
+            public_key: vec![],
+        };
+        let resp: PublicKeyResponse = signer.send(signer.client.get(signer.key_url(""))).await
+            .with_context(|| format!("Failed to get public key of {} from {}", &signer.key_id, &signer.url))?;
+        signer.public_key = hex::decode(&resp.public_key).context("Invalid public key")?;
+        Ok(signer)
+    }
+
+    pub async fn sign_tx(&self, tx: &TypedTransaction, chain_id: u64) -> Result<Signature> {
+        let request = SignTxRequest { chain_id, tx: tx.clone() };
+        let resp: SignTxResponse = self.send(self.client.post(self.key_url("/sign_tx")).json(&request)).await
+            .with_context(|| format!("Failed to sign tx with {}", &self.key_id))?;
+        Ok(resp.signature)
+    }
+
+    fn key_url(&self, path: &str) -> String {
+        format!("{}/keys/{}{}", &self.url, urlencoding::encode(&self.key_id), path)
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(&self, request: RequestBuilder) -> Result<T> {
+        let request = match &self.auth_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        let resp = request.send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(anyhow!("remote signer returned {}: {}", status, resp.text().await.unwrap_or_default()))
+        }
+        Ok(resp.json().await?)
+    }
+}
+
+#[async_trait]
+impl DigestSigner for RemoteSigner {
+    fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    async fn sign_digest(&self, digest: [u8; 32]) -> Result<DigestSignature> {
+        let request = SignDigestRequest { digest: hex::encode_prefixed(digest) };
+        let resp: SignDigestResponse = self.send(self.client.post(self.key_url("/sign_digest")).json(&request)).await
+            .with_context(|| format!("Failed to sign digest with {}", &self.key_id))?;
+        let signature = hex::decode(&resp.signature).context("Invalid signature")?;
+        if signature.len() != 64 {
+            return Err(anyhow!("remote signer returned a signature of {} bytes", signature.len()))
+        }
+        Ok(DigestSignature { signature, recovery_id: resp.recovery_id })
+    }
+}
@@ -0,0 +1,67 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use tokio::task::JoinSet;
+use tokio::time::sleep;
+use tracing::{error, warn};
+
+use crate::conf::Supervisor;
+use crate::health;
+use crate::metrics::TASK_RESTARTS;
+use crate::shutdown;
+
+// runs a long-running task and restarts it with exponential backoff whenever it fails, panics or returns.
+// returns an error once the task has failed `max_restarts` times in a row so that the caller can escalate.
+pub async fn supervise<F, Fut>(name: String, conf: Supervisor, task: F) -> Result<()>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output=Result<()>> + Send + 'static,
+{
+    let mut failures = 0;
+    loop {
+        let started_at = Instant::now();
+        let reason = match tokio::spawn(task()).await {
+            Ok(Ok(_)) => anyhow!("task exited"),
+            Ok(Err(e)) => e,
+            Err(e) => anyhow!("task panicked: {:?}", e),
+        };
+        if shutdown::is_requested() {
+            return Ok(())
+        }
+
+        if started_at.elapsed() >= Duration::from_secs(conf.reset_after_secs) {
+            failures = 0;
+        }
+        failures += 1;
+        if failures > conf.max_restarts {
+            health::set_restarting(&name, false);
+            error!("Task {} failed {} times in a row, giving up: {:?}", &name, failures, reason);
+            return Err(reason.context(format!("task {} failed {} times in a row", &name, failures)))
+        }
+
+        let backoff = backoff(&conf, failures);
+        TASK_RESTARTS.with_label_values(&[&name]).inc();
+        // the task stays live while it is restarted, so that a liveness probe does not kill the process over it
+        health::set_restarting(&name, true);
+        warn!("Task {} failed ({}/{}): {:?}, restarting in {:?}", &name, failures, conf.max_restarts, reason, backoff);
+        sleep(backoff).await;
+    }
+}
+
+// waits on a set of supervised tasks and returns the first escalated failure
+pub async fn wait_all(mut tasks: JoinSet<Result<()>>) -> Result<()> {
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => return Err(e),
+            Err(e) => return Err(anyhow!("supervisor panicked: {:?}", e)),
+        }
+    }
+    Ok(())
+}
+
+fn backoff(conf: &Supervisor, failures: u32) -> Duration {
+    let secs = conf.initial_backoff_secs.saturating_mul(2u64.saturating_pow(failures.saturating_sub(1)));
+    Duration::from_secs(secs.min(conf.max_backoff_secs))
+}
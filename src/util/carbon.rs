@@ -1,4 +1,6 @@
 pub mod msg;
 pub mod parser;
 pub mod tx;
-pub mod query;
\ No newline at end of file
+pub mod query;
+pub mod grpc;
+pub mod bridge;
\ No newline at end of file
@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Context, Result};
+use cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
+use tonic::transport::Channel;
+
+use crate::switcheo::carbon::bridge::{Connection, ExternalTokenMapping, Params, QueryAllConnectionsRequest, QueryAllExternalTokenMappingsRequest, QueryConnectionRequest, QueryParamsRequest, QueryPendingActionNoncesRequest, QueryPendingActionRequest, QueryPendingActionResponse};
+use crate::switcheo::carbon::bridge::query_client::QueryClient;
+use crate::util::carbon::grpc::channel;
+
+// typed client for the bridge module's Query service
+#[derive(Clone)]
+pub struct BridgeQueryClient {
+    client: QueryClient<Channel>,
+}
+
+impl BridgeQueryClient {
+    pub fn new(grpc_url: &str) -> Result<Self> {
+        Ok(BridgeQueryClient { client: QueryClient::new(channel(grpc_url)?) })
+    }
+
+    pub async fn params(&self) -> Result<Params> {
+        let resp = self.client.clone().params(QueryParamsRequest {}).await
+            .context("Failed to query bridge params")?
+            .into_inner();
+        resp.params.ok_or_else(|| anyhow!("params not found"))
+    }
+
+    pub async fn connection(&self, connection_id: &str) -> Result<Connection> {
+        let request = QueryConnectionRequest { connection_id: connection_id.to_string() };
+        let resp = self.client.clone().connection(request).await
+            .with_context(|| format!("Failed to query connection {}", connection_id))?
+            .into_inner();
+        resp.connection.ok_or_else(|| anyhow!("connection {} not found", connection_id))
+    }
+
+    pub async fn connections(&self) -> Result<Vec<Connection>> {
+        let mut connections = Vec::new();
+        let mut next_key = Vec::new();
+        loop {
+            let request = QueryAllConnectionsRequest { pagination: Some(page_request(next_key)) };
+            let resp = self.client.clone().connection_all(request).await
+                .context("Failed to query connections")?
+                .into_inner();
+            connections.extend(resp.connections);
+            match resp.pagination {
+                Some(page) if !page.next_key.is_empty() => next_key = page.next_key,
+                _ => return Ok(connections),
+            }
+        }
+    }
+
+    pub async fn external_token_mappings(&self, connection_id: &str) -> Result<Vec<ExternalTokenMapping>> {
+        let mut mappings = Vec::new();
+        let mut next_key = Vec::new();
+        loop {
+            let request = QueryAllExternalTokenMappingsRequest {
+                connection_id: connection_id.to_string(),
+                pagination: Some(page_request(next_key)),
+            };
+            let resp = self.client.clone().external_token_mapping_all(request).await
+                .with_context(|| format!("Failed to query external tokens of {}", connection_id))?
+                .into_inner();
+            mappings.extend(resp.external_token_mappings);
+            match resp.pagination {
+                Some(page) if !page.next_key.is_empty() => next_key = page.next_key,
+                _ => return Ok(mappings),
+            }
+        }
+    }
+
+    pub async fn pending_action(&self, nonce: u64) -> Result<QueryPendingActionResponse> {
+        let resp = self.client.clone().pending_action(QueryPendingActionRequest { nonce }).await
+            .with_context(|| format!("Failed to query pending action {}", nonce))?
+            .into_inner();
+        Ok(resp)
+    }
+
+    pub async fn pending_action_nonces(&self) -> Result<Vec<u64>> {
+        let resp = self.client.clone().pending_action_nonces(QueryPendingActionNoncesRequest {}).await
+            .context("Failed to query pending action nonces")?
+            .into_inner();
+        Ok(resp.pending_action_nonces)
+    }
+}
+
+fn page_request(key: Vec<u8>) -> PageRequest {
+    PageRequest { key, limit: 100, ..Default::default() }
+}
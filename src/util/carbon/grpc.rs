@@ -0,0 +1,113 @@
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Context, Result};
+use cosmos_sdk_proto::cosmos::auth::v1beta1::{BaseAccount, QueryAccountRequest};
+use cosmos_sdk_proto::cosmos::auth::v1beta1::query_client::QueryClient as AuthQueryClient;
+use cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use cosmos_sdk_proto::cosmos::tx::v1beta1::{BroadcastMode, BroadcastTxRequest, GetTxRequest, SimulateRequest};
+use cosmos_sdk_proto::cosmos::tx::v1beta1::service_client::ServiceClient;
+use prost::Message;
+use tonic::Code;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tracing::info;
+
+use crate::util::cosmos::{Attribute, DeliveredTx, Event};
+
+// one channel is shared by all requests, it connects on first use and reconnects by itself
+static CHANNEL: OnceLock<Channel> = OnceLock::new();
+
+// accounts that embed a BaseAccount as their first field, e.g. eth accounts
+#[derive(Clone, PartialEq, Message)]
+struct WrappedAccount {
+    #[prost(message, optional, tag = "1")]
+    base_account: Option<BaseAccount>,
+}
+
+pub fn channel(grpc_url: &str) -> Result<Channel> {
+    if let Some(channel) = CHANNEL.get() {
+        return Ok(channel.clone())
+    }
+    let mut endpoint = Endpoint::from_shared(grpc_url.to_string())
+        .with_context(|| format!("Invalid grpc_url {:?}", grpc_url))?;
+    if grpc_url.starts_with("https://") {
+        endpoint = endpoint.tls_config(ClientTlsConfig::new()).context("Failed to configure grpc tls")?;
+    }
+    Ok(CHANNEL.get_or_init(|| endpoint.connect_lazy()).clone())
+}
+
+pub async fn get_account_info(grpc_url: &str, account_address: &str) -> Result<(u64, u64)> {
+    let mut client = AuthQueryClient::new(channel(grpc_url)?);
+    let resp = client.account(QueryAccountRequest { address: account_address.to_string() }).await
+        .context("Failed to query account")?
+        .into_inner();
+    let account = resp.account.ok_or_else(|| anyhow!("account not found"))?;
+    let base_account = if account.type_url == "/cosmos.auth.v1beta1.BaseAccount" {
+        BaseAccount::decode(account.value.as_slice())?
+    } else {
+        WrappedAccount::decode(account.value.as_slice())?.base_account
+            .ok_or_else(|| anyhow!("unsupported account type {}", account.type_url))?
+    };
+    info!("found account number {:?}, with sequence {:?}", base_account.account_number, base_account.sequence);
+    Ok((base_account.account_number, base_account.sequence))
+}
+
+// broadcasts a tx and returns its hash once it passed CheckTx
+pub async fn broadcast_tx(grpc_url: &str, tx_bytes: Vec<u8>) -> Result<String> {
+    let mut client = ServiceClient::new(channel(grpc_url)?);
+    let request = BroadcastTxRequest { tx_bytes, mode: BroadcastMode::Sync.into() };
+    let resp = client.broadcast_tx(request).await
+        .context("Failed to broadcast tx")?
+        .into_inner();
+    let tx_response = resp.tx_response.ok_or_else(|| anyhow!("tx_response not found"))?;
+    if tx_response.code != 0 {
+        return Err(anyhow!("tx failed with code {} ({}): {}", tx_response.code, &tx_response.codespace, &tx_response.raw_log))
+    }
+    info!("tx sent successfully: {}", &tx_response.txhash);
+    Ok(tx_response.txhash)
+}
+
+// gas used by the tx when simulated against the latest state
+pub async fn simulate_tx(grpc_url: &str, tx_bytes: Vec<u8>) -> Result<u64> {
+    let mut client = ServiceClient::new(channel(grpc_url)?);
+    #[allow(deprecated)]
+    let request = SimulateRequest { tx: None, tx_bytes };
+    let resp = client.simulate(request).await
+        .context("Failed to simulate tx")?
+        .into_inner();
+    let gas_info = resp.gas_info.ok_or_else(|| anyhow!("gas_info not found"))?;
+    Ok(gas_info.gas_used)
+}
+
+// the tx once it is included in a block, it is not found before that
+pub async fn get_tx(grpc_url: &str, tx_hash: &str) -> Result<Option<DeliveredTx>> {
+    let mut client = ServiceClient::new(channel(grpc_url)?);
+    match client.get_tx(GetTxRequest { hash: tx_hash.to_string() }).await {
+        Ok(resp) => Ok(resp.into_inner().tx_response.map(delivered_tx)),
+        Err(status) if status.code() == Code::NotFound => Ok(None),
+        Err(status) => Err(status).context("Failed to get tx"),
+    }
+}
+
+fn delivered_tx(tx_response: TxResponse) -> DeliveredTx {
+    let events = tx_response.events.into_iter()
+        .map(|event| Event {
+            event_type: event.r#type,
+            attributes: event.attributes.into_iter()
+                .map(|attribute| Attribute {
+                    index: attribute.index,
+                    key: String::from_utf8_lossy(attribute.key.as_ref()).to_string(),
+                    value: String::from_utf8_lossy(attribute.value.as_ref()).to_string(),
+                })
+                .collect(),
+        })
+        .collect();
+    DeliveredTx {
+        tx_hash: tx_response.txhash,
+        height: tx_response.height,
+        code: tx_response.code,
+        codespace: tx_response.codespace,
+        raw_log: tx_response.raw_log,
+        gas_used: tx_response.gas_used,
+        events,
+    }
+}
@@ -7,7 +7,7 @@ use ethers::utils::keccak256;
 use num_traits::ToPrimitive;
 use sqlx::types::BigDecimal;
 
-use crate::db::{BridgeRevertedEvent, DbAxelarCallContractEvent, DbPendingActionEvent, ExpiredPendingActionEvent};
+use crate::db::{BridgeRevertedEvent, DbAxelarCallContractEvent, DbPendingActionEvent, ExpiredPendingActionEvent, RelayStatus};
 use crate::util::cosmos::Event;
 
 pub fn strip_quotes(input: &str) -> &str {
@@ -40,6 +40,11 @@ pub fn parse_bridge_pending_action_event(event: Event) -> DbPendingActionEvent {
         pending_action_type,
         retry_count: 0,
         relay_details,
+        status: RelayStatus::Observed.to_string(),
+        status_timestamps: serde_json::json!({}),
+        status_reason: None,
+        terminated_at_height: None,
+        terminated_by_tx_hash: None,
     }
 }
 
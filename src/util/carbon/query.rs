@@ -1,13 +1,21 @@
 use anyhow::{anyhow, Context, Result};
 use reqwest::Client;
-use serde_json::{from_value, Value};
-use tracing::{debug};
-use crate::db::{DbPendingActionEvent, PendingActionType, RelayDetails};
+use serde_json::{from_value, json, Value};
+use tracing::debug;
+use crate::conf::Carbon;
+use crate::db::{DbPendingActionEvent, PendingActionType, RelayDetails, RelayStatus};
+use crate::util::carbon::bridge::BridgeQueryClient;
 use crate::util::carbon::parser::parse_connection_id;
 
-pub async fn get_pending_action_nonces(rest_url: &str) -> Result<Vec<i64>> {
+pub async fn get_pending_action_nonces(conf: &Carbon) -> Result<Vec<i64>> {
+    if let Some(grpc_url) = &conf.grpc_url {
+        let nonces = BridgeQueryClient::new(grpc_url)?.pending_action_nonces().await?;
+        debug!("found pending action nonces {:?}", nonces);
+        return Ok(nonces.into_iter().map(|nonce| nonce as i64).collect())
+    }
+
     let client = Client::new();
-    let url = format!("{}/carbon/bridge/v1/pending_action_nonce", rest_url);
+    let url = format!("{}/carbon/bridge/v1/pending_action_nonce", &conf.rest_url);
     let resp: Value = client.get(&url).send().await?.json().await?;
 
     // Parse the nonces array
@@ -22,16 +30,25 @@ pub async fn get_pending_action_nonces(rest_url: &str) -> Result<Vec<i64>> {
     Ok(nonces)
 }
 
-pub async fn get_pending_action(rest_url: &str, nonce: i64) -> Result<DbPendingActionEvent> {
-    let client = Client::new();
-    let url = format!("{}/carbon/bridge/v1/pending_action/{}", rest_url, nonce);
-    let resp: Value = client.get(&url).send().await?.json().await?;
+// the json decoded action, including its method, connection_id and relay_details
+async fn query_pending_action(conf: &Carbon, nonce: i64) -> Result<Value> {
+    let action_str = match &conf.grpc_url {
+        Some(grpc_url) => BridgeQueryClient::new(grpc_url)?.pending_action(nonce as u64).await?.action,
+        None => {
+            let client = Client::new();
+            let url = format!("{}/carbon/bridge/v1/pending_action/{}", &conf.rest_url, nonce);
+            let resp: Value = client.get(&url).send().await?.json().await?;
+            resp["action"]
+                .as_str()
+                .context("Failed to get action as string")?
+                .to_string()
+        }
+    };
+    serde_json::from_str(&action_str).context("Failed to deserialize action")
+}
 
-    let action_str = resp["action"]
-        .as_str()
-        .context("Failed to get action as string")?;
-    let action: Value = serde_json::from_str(action_str)
-        .context("Failed to deserialize action")?;
+pub async fn get_pending_action(conf: &Carbon, nonce: i64) -> Result<DbPendingActionEvent> {
+    let action = query_pending_action(conf, nonce).await?;
 
     // extract connection_id and relay_details from action
     let connection_id = action["connection_id"]
@@ -41,8 +58,23 @@ pub async fn get_pending_action(rest_url: &str, nonce: i64) -> Result<DbPendingA
 
     let relay_details = action["relay_details"].clone();
 
-    // parse bridge_id + chain_id from connection_id
-    let (bridge_id, chain_id, _) = parse_connection_id(&connection_id);
+    // bridge_id + chain_id from the connection, or parsed from connection_id without grpc
+    let (bridge_id, chain_id) = match &conf.grpc_url {
+        Some(grpc_url) => {
+            let connection = BridgeQueryClient::new(grpc_url)?.connection(&connection_id).await?;
+            (connection.bridge_id.to_string(), connection.chain_id)
+        }
+        None => {
+            let (bridge_id, chain_id, _) = parse_connection_id(&connection_id);
+            (bridge_id, chain_id)
+        }
+    };
+
+    // the type is not guessed, an action with an unknown method is left for the listener to pick up
+    let method = action["method"]
+        .as_str()
+        .with_context(|| format!("Missing method on pending action {}", nonce))?;
+    let pending_action_type = PendingActionType::from_prefix(method)?;
 
     Ok(DbPendingActionEvent {
         id: -1, // it's not from DB yet
@@ -50,24 +82,19 @@ pub async fn get_pending_action(rest_url: &str, nonce: i64) -> Result<DbPendingA
         bridge_id,
         chain_id,
         nonce,
-        // TODO: pending_action_type is currently hardcoded in this function because the endpoint doesn't return the required information
-        // TODO: uncomment below after fix to un-hardcode the action type: https://test-api.carbon.network/carbon/bridge/v1/pending_action/42
-        // pending_action_type: PendingActionType::from_prefix(action["method"].as_str().unwrap_or_default())?.into(),
-        pending_action_type: PendingActionType::PendingWithdrawType.into(),
+        pending_action_type: pending_action_type.into(),
         retry_count: 0,
         relay_details,
+        status: RelayStatus::Observed.to_string(),
+        status_timestamps: json!({}),
+        status_reason: None,
+        terminated_at_height: None,
+        terminated_by_tx_hash: None,
     })
 }
 
-pub async fn get_pending_action_relay_details(rest_url: &str, nonce: i64) -> Result<RelayDetails> {
-    let client = Client::new();
-    let url = format!("{}/carbon/bridge/v1/pending_action/{}", rest_url, nonce);
-    let resp: Value = client.get(&url).send().await?.json().await?;
-    let action_str = resp["action"]
-        .as_str()
-        .context("Failed to get action as string")?;
-    let action: Value = serde_json::from_str(action_str)
-        .context("Failed to deserialize action")?;
+pub async fn get_pending_action_relay_details(conf: &Carbon, nonce: i64) -> Result<RelayDetails> {
+    let action = query_pending_action(conf, nonce).await?;
     let relay_details: RelayDetails = from_value(action["relay_details"].clone()).expect("cannot parse relay_details");
     Ok(relay_details)
-}
\ No newline at end of file
+}
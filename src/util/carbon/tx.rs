@@ -1,65 +1,250 @@
 use std::str::FromStr;
+use std::time::Duration;
 
-use anyhow::Result;
-use bip32::DerivationPath;
+use anyhow::{Context, Result};
+use bip32::{DerivationPath, XPrv};
 use bip39::Mnemonic;
 use cosmrs::{Coin, tx};
-use cosmrs::crypto::secp256k1;
+use cosmrs::crypto::PublicKey;
+use cosmrs::proto::cosmos::tx::v1beta1::TxRaw;
 use cosmrs::tendermint::block::Height;
 use cosmrs::tx::{Fee, SignDoc, SignerInfo};
+use k256::ecdsa::SigningKey;
 use prost_types::Any;
-use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::sync::{Mutex, OnceCell};
+use tokio::time::{Instant, sleep};
+use tracing::{debug, info, warn};
 
 use crate::conf::Carbon;
-use crate::util::cosmos::{get_account_info, get_latest_block_height, send_transaction};
+use crate::signer::{DigestSigner, LocalSigner};
+use crate::signer::remote::RemoteSigner;
+use crate::util::carbon::grpc;
+use crate::util::cosmos::{DeliveredTx, get_account_info, get_latest_block_height, get_tx, send_transaction, simulate_transaction, TxNotIncluded};
 
 const COSMOS_HD_PATH: &str = "m/44'/118'/0'/0/0";
 
+const TX_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// retries after an account sequence mismatch, with the sequence the chain expects
+const MAX_SEQUENCE_RETRIES: usize = 3;
+
 pub async fn send_msg_via_tx(
     conf: &Carbon,
     msg: impl Into<Any>
-) -> Result<Value> {
-    let tx_bytes = create_signed_tx(&conf, msg).await?;
+) -> Result<DeliveredTx> {
+    send_msgs_via_tx(conf, vec![msg.into()]).await
+}
+
+// account number and next sequence of the relayer, kept locally so that txs can be sent back to back
+// without waiting for the rest api to see the previous tx. the lock also keeps txs from being signed concurrently
+static ACCOUNT: Mutex<Option<(u64, u64)>> = Mutex::const_new(None);
+
+// sends all msgs in one tx and waits for it to be included, the msgs succeed or fail together.
+// a tx that fails in DeliverTx is returned with its non-zero code
+pub async fn send_msgs_via_tx(conf: &Carbon, msgs: Vec<Any>) -> Result<DeliveredTx> {
+    let tx_hash = broadcast_msgs(conf, msgs).await?;
+    let timeout = Duration::from_secs(conf.tx_inclusion_timeout_secs.unwrap_or(60));
+    let delivered = wait_for_tx(conf, &tx_hash, timeout).await?;
+    if delivered.is_success() {
+        info!("tx {} included at height {}", &delivered.tx_hash, delivered.height);
+    } else {
+        warn!("tx {} failed at height {} with code {} ({}): {}", &delivered.tx_hash, delivered.height, delivered.code, &delivered.codespace, &delivered.raw_log);
+    }
+    Ok(delivered)
+}
+
+// polls the tx until it is included in a block, a tx that is still unknown after the timeout is an error.
+// the tx already passed CheckTx, so failed lookups are retried and end up as TxNotIncluded rather than a failed tx
+async fn wait_for_tx(conf: &Carbon, tx_hash: &str, timeout: Duration) -> Result<DeliveredTx> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let tx = match &conf.grpc_url {
+            Some(grpc_url) => grpc::get_tx(grpc_url, tx_hash).await,
+            None => get_tx(&conf.rest_url, tx_hash).await,
+        };
+        match tx {
+            Ok(Some(tx)) => return Ok(tx),
+            Ok(None) => {}
+            Err(e) => warn!("Failed to look up tx {}, retrying: {:?}", tx_hash, e),
+        }
+        if Instant::now() >= deadline {
+            return Err(TxNotIncluded { tx_hash: tx_hash.to_string(), timeout }.into())
+        }
+        sleep(TX_POLL_INTERVAL).await;
+    }
+}
 
-    // send tx
-    send_transaction(&conf.rest_url, tx_bytes).await
+// signs and broadcasts the msgs in one tx, returning its hash once the tx passed CheckTx
+async fn broadcast_msgs(conf: &Carbon, msgs: Vec<Any>) -> Result<String> {
+    let signer = carbon_signer(conf).await?;
+    let public_key = PublicKey::from_raw_secp256k1(&signer.public_key())
+        .context("Invalid relayer public key")?;
+    let account_id = public_key.account_id(&conf.account_prefix).unwrap();
+
+    let mut account = ACCOUNT.lock().await;
+    let mut retries = 0;
+    loop {
+        let (account_number, sequence) = match *account {
+            Some(account) => account,
+            None => match &conf.grpc_url {
+                Some(grpc_url) => grpc::get_account_info(grpc_url, &account_id.to_string()).await?,
+                None => get_account_info(&conf.rest_url, &account_id.to_string()).await?,
+            },
+        };
+        let tx_bytes = create_signed_tx(conf, signer, public_key, msgs.clone(), account_number, sequence).await?;
+
+        // send tx
+        let result = match &conf.grpc_url {
+            Some(grpc_url) => grpc::broadcast_tx(grpc_url, tx_bytes).await,
+            None => send_transaction(&conf.rest_url, tx_bytes).await.and_then(|response| {
+                Ok(response["tx_response"]["txhash"].as_str().context("txhash not found")?.to_string())
+            }),
+        };
+        match result {
+            Ok(tx_hash) => {
+                *account = Some((account_number, sequence + 1));
+                return Ok(tx_hash)
+            }
+            Err(e) if e.to_string().contains("account sequence mismatch") && retries < MAX_SEQUENCE_RETRIES => {
+                let expected = expected_sequence(&e.to_string());
+                warn!("Account sequence mismatch at sequence {}, retrying with {:?}", sequence, expected);
+                // refetched when the expected sequence cannot be parsed
+                *account = expected.map(|expected| (account_number, expected));
+                retries += 1;
+            }
+            Err(e) => {
+                // the sequence may or may not have been used, it is fetched again for the next tx
+                *account = None;
+                return Err(e)
+            }
+        }
+    }
+}
+
+// the sequence from an error like "account sequence mismatch, expected 12, got 11"
+fn expected_sequence(error: &str) -> Option<u64> {
+    let (_, rest) = error.split_once("account sequence mismatch, expected ")?;
+    rest.chars().take_while(|c| c.is_ascii_digit()).collect::<String>().parse().ok()
 }
 
-async fn create_signed_tx(conf: &Carbon, msg: impl Into<Any>) -> Result<Vec<u8>> {
-// Generate private key from mnemonic
-    let mnemonic = Mnemonic::parse(&conf.relayer_mnemonic)?;
+// the signer is created once as keystores are slow to decrypt by design
+static CARBON_SIGNER: OnceCell<Box<dyn DigestSigner>> = OnceCell::const_new();
 
+async fn carbon_signer(conf: &Carbon) -> Result<&'static dyn DigestSigner> {
+    let signer = CARBON_SIGNER.get_or_try_init(|| async {
+        let signer: Box<dyn DigestSigner> = match &conf.remote_signer {
+            Some(remote) => Box::new(RemoteSigner::connect(remote).await?),
+            None => {
+                let mnemonic = match &conf.relayer_mnemonic_keystore {
+                    Some(keystore) => {
+                        let keystore = keystore.clone();
+                        let mnemonic = tokio::task::spawn_blocking(move || keystore.decrypt()).await??;
+                        String::from_utf8(mnemonic).context("Mnemonic keystore is not valid utf8")?
+                    }
+                    None => conf.relayer_mnemonic.clone(),
+                };
+                Box::new(LocalSigner::new(derive_key(&mnemonic)?))
+            }
+        };
+        Ok::<_, anyhow::Error>(signer)
+    }).await?;
+    Ok(signer.as_ref())
+}
+
+// private key from a mnemonic with the cosmos hd path
+pub fn derive_key(mnemonic: &str) -> Result<SigningKey> {
+    let mnemonic = Mnemonic::parse(mnemonic)?;
     let seed = mnemonic.to_seed("");
     let derivation_path = DerivationPath::from_str(COSMOS_HD_PATH)?;
-    let sender_private_key = secp256k1::SigningKey::derive_from_path(&seed, &derivation_path).expect("private key could not be derived");
-
-    let sender_public_key = sender_private_key.public_key();
-    let sender_account_id = sender_public_key.account_id(&conf.account_prefix).unwrap();
+    let key = XPrv::derive_from_path(seed, &derivation_path).context("private key could not be derived")?;
+    Ok(key.private_key().clone())
+}
 
-    let (account_number, sequence) = get_account_info(&conf.rest_url, &sender_account_id.to_string()).await?;
+// the fee paid when the tx cannot be simulated
+const FALLBACK_FEE_AMOUNT: u128 = 100000000;
+const FALLBACK_GAS: u64 = 1200000000;
 
+async fn create_signed_tx(conf: &Carbon, signer: &dyn DigestSigner, sender_public_key: PublicKey, msgs: Vec<Any>, account_number: u64, sequence: u64) -> Result<Vec<u8>> {
     let chain_id = conf.chain_id.parse().unwrap();
 
     let signer_info = SignerInfo::single_direct(Some(sender_public_key.into()), sequence);
 
-    // set hard-coded gas values into auth_info
-    let denom = conf.fee_denom.clone().unwrap_or_else(|| "swth".to_string());
-    let fee_coin = Coin::new(100000000, &denom)
-        .expect("unable to parse fee denom");
-    let default_gas: u64 = 1000000000;
-    let gas_multiplier: f64 = 1.2;
-    let adjusted_gas = (default_gas as f64 * gas_multiplier) as u64;
-    let auth_info = signer_info.clone().auth_info(Fee::from_amount_and_gas(fee_coin.clone(), adjusted_gas));
-
     // add timeout height
     let latest_block_height = get_latest_block_height(&conf.rpc_url).await?;
     let timeout_height = latest_block_height + 100; // Set timeout height to current height + 100
     let timeout_height = Height::try_from(timeout_height)?;
 
     // create tx
-    let tx_body = tx::BodyBuilder::new().msg(msg).timeout_height(timeout_height).finish();
+    let tx_body = tx::BodyBuilder::new().msgs(msgs).timeout_height(timeout_height).finish();
+    let fee = estimate_fee(conf, &tx_body, &signer_info).await?;
+    let auth_info = signer_info.auth_info(fee);
     let sign_doc = SignDoc::new(&tx_body, &auth_info, &chain_id, account_number).expect("signdoc failed");
-    let tx_signed = sign_doc.sign(&sender_private_key).expect("signing failed");
-    let tx_bytes = tx_signed.to_bytes().expect("to_bytes failed");
+    let sign_doc_bytes = sign_doc.clone().into_bytes().expect("signdoc encoding failed");
+    let signature = signer.sign_digest(Sha256::digest(&sign_doc_bytes).into()).await?;
+    let tx_raw = TxRaw {
+        body_bytes: sign_doc.body_bytes,
+        auth_info_bytes: sign_doc.auth_info_bytes,
+        signatures: vec![signature.signature],
+    };
+    let tx_bytes = tx::Raw::from(tx_raw).to_bytes().expect("to_bytes failed");
     Ok(tx_bytes)
 }
+
+// gas from a simulation of the tx times gas_adjustment, paid at gas_price
+async fn estimate_fee(conf: &Carbon, tx_body: &tx::Body, signer_info: &SignerInfo) -> Result<Fee> {
+    let denom = conf.fee_denom.clone().unwrap_or_else(|| "swth".to_string());
+    let gas_used = match simulate_tx(conf, tx_body, signer_info).await {
+        Ok(gas_used) => gas_used,
+        Err(e) => {
+            warn!("Failed to simulate tx, paying the fallback fee of {}{}: {:?}", FALLBACK_FEE_AMOUNT, &denom, e);
+            let fee_coin = Coin::new(FALLBACK_FEE_AMOUNT, &denom)
+                .expect("unable to parse fee denom");
+            return Ok(Fee::from_amount_and_gas(fee_coin, FALLBACK_GAS))
+        }
+    };
+    let gas_limit = (gas_used as f64 * conf.gas_adjustment.unwrap_or(1.5)).ceil() as u64;
+    let amount = (gas_limit as f64 * conf.gas_price.unwrap_or(0.1)).ceil() as u128;
+    debug!("Simulated tx used {} gas, paying {}{} for a gas limit of {}", gas_used, amount, &denom, gas_limit);
+    let fee_coin = Coin::new(amount, &denom)
+        .expect("unable to parse fee denom");
+    Ok(Fee::from_amount_and_gas(fee_coin, gas_limit))
+}
+
+// simulations do not check signatures, so the tx is simulated unsigned to save a round trip to a remote signer
+async fn simulate_tx(conf: &Carbon, tx_body: &tx::Body, signer_info: &SignerInfo) -> Result<u64> {
+    let denom = conf.fee_denom.clone().unwrap_or_else(|| "swth".to_string());
+    let fee_coin = Coin::new(0, &denom)
+        .expect("unable to parse fee denom");
+    let auth_info = signer_info.clone().auth_info(Fee::from_amount_and_gas(fee_coin, 0u64));
+    let tx_raw = TxRaw {
+        body_bytes: tx_body.clone().into_bytes().expect("tx body encoding failed"),
+        auth_info_bytes: auth_info.into_bytes().expect("auth info encoding failed"),
+        signatures: vec![vec![]],
+    };
+    let tx_bytes = tx::Raw::from(tx_raw).to_bytes().expect("to_bytes failed");
+    match &conf.grpc_url {
+        Some(grpc_url) => grpc::simulate_tx(grpc_url, tx_bytes).await,
+        None => simulate_transaction(&conf.rest_url, tx_bytes).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_expected_sequence() {
+        let error = "account sequence mismatch, expected 12, got 11: incorrect account sequence";
+        assert_eq!(expected_sequence(error), Some(12));
+        let error = "broadcast failed: code 32: account sequence mismatch, expected 1034, got 1031";
+        assert_eq!(expected_sequence(error), Some(1034));
+    }
+
+    #[test]
+    fn missing_expected_sequence_is_none() {
+        assert_eq!(expected_sequence("account sequence mismatch"), None);
+        assert_eq!(expected_sequence("account sequence mismatch, expected , got 11"), None);
+        assert_eq!(expected_sequence("insufficient fees"), None);
+    }
+}
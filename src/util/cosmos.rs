@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
 use anyhow::{anyhow, Context, Result};
 use base64::{Engine};
 use base64::engine::general_purpose;
@@ -18,6 +22,8 @@ pub struct WebSocketMessage {
 pub struct WsResult {
     pub query: String,
     pub data: Data,
+    #[serde(default)]
+    pub events: HashMap<String, Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -57,6 +63,7 @@ pub struct Event {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Attribute {
+    #[serde(default)]
     pub index: bool,
     pub key: String,
     pub value: String,
@@ -74,6 +81,27 @@ pub fn extract_events(msg: &str, event_name: &str) -> anyhow::Result<Vec<Event>>
     Ok(events)
 }
 
+// Extracts the height and hash of the tx that emitted the events in a JSON message
+pub fn extract_tx_info(msg: &str) -> anyhow::Result<(Option<i64>, Option<String>)> {
+    let query_response = serde_json::from_str::<WebSocketMessage>(msg)
+        .with_context(|| format!("Failed to parse JSON, provided string was: {}", msg))?;
+
+    let height = query_response.result.data.value.tx_result.height.parse::<i64>().ok();
+    let tx_hash = query_response.result.events.get("tx.hash")
+        .and_then(|hashes| hashes.first().cloned());
+    Ok((height, tx_hash))
+}
+
+// Extracts the height from a NewBlockHeader JSON message
+pub fn extract_block_height(msg: &str) -> anyhow::Result<i64> {
+    let json_msg: serde_json::Value = serde_json::from_str(msg)
+        .with_context(|| format!("Failed to parse JSON, provided string was: {}", msg))?;
+    let height = json_msg["result"]["data"]["value"]["header"]["height"]
+        .as_str()
+        .ok_or_else(|| anyhow!("height not found"))?
+        .parse::<i64>()?;
+    Ok(height)
+}
 
 pub async fn get_account_info(rest_url: &str, account_address: &str) -> Result<(u64, u64)> {
     let client = Client::new();
@@ -103,7 +131,86 @@ pub async fn get_latest_block_height(rpc_url: &str) -> Result<u64> {
     Ok(block_height)
 }
 
-// sends a tx and returns the json response
+// result of a tx once it is included in a block
+#[derive(Clone, Debug)]
+pub struct DeliveredTx {
+    pub tx_hash: String,
+    pub height: i64,
+    // deliver tx code, 0 if the msgs succeeded
+    pub code: u32,
+    pub codespace: String,
+    pub raw_log: String,
+    pub gas_used: i64,
+    pub events: Vec<Event>,
+}
+
+impl DeliveredTx {
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
+}
+
+// the tx passed CheckTx but was not seen in a block in time, it can still be included later
+#[derive(Debug, Clone)]
+pub struct TxNotIncluded {
+    pub tx_hash: String,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for TxNotIncluded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tx {} was not included within {:?}", self.tx_hash, self.timeout)
+    }
+}
+
+impl std::error::Error for TxNotIncluded {}
+
+// the tx once it is included in a block, it is not found before that
+pub async fn get_tx(rest_url: &str, tx_hash: &str) -> Result<Option<DeliveredTx>> {
+    let client = Client::new();
+    let url = format!("{}/cosmos/tx/v1beta1/txs/{}", rest_url, tx_hash);
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        return Ok(None)
+    }
+    let resp: serde_json::Value = resp.json().await?;
+    Ok(Some(parse_tx_response(&resp["tx_response"])?))
+}
+
+fn parse_tx_response(tx_response: &serde_json::Value) -> Result<DeliveredTx> {
+    let parse_i64 = |value: &serde_json::Value| value.as_str().and_then(|value| value.parse::<i64>().ok()).unwrap_or_default();
+    Ok(DeliveredTx {
+        tx_hash: tx_response["txhash"].as_str().ok_or_else(|| anyhow!("txhash not found"))?.to_string(),
+        height: parse_i64(&tx_response["height"]),
+        code: tx_response["code"].as_u64().ok_or_else(|| anyhow!("code not found"))? as u32,
+        codespace: tx_response["codespace"].as_str().unwrap_or_default().to_string(),
+        raw_log: tx_response["raw_log"].as_str().unwrap_or_default().to_string(),
+        gas_used: parse_i64(&tx_response["gas_used"]),
+        events: serde_json::from_value(tx_response["events"].clone()).unwrap_or_default(),
+    })
+}
+
+// gas used by the tx when simulated against the latest state
+pub async fn simulate_transaction(rest_url: &str, tx_bytes: Vec<u8>) -> Result<u64> {
+    let client = Client::new();
+    let payload = json!({
+        "tx_bytes": general_purpose::STANDARD.encode(&tx_bytes),
+    });
+    let response: serde_json::Value = client
+        .post(format!("{}/cosmos/tx/v1beta1/simulate", rest_url))
+        .json(&payload)
+        .send()
+        .await?
+        .json()
+        .await?;
+    let gas_used = response["gas_info"]["gas_used"]
+        .as_str()
+        .ok_or_else(|| anyhow!("simulation failed: {}", response))?
+        .parse::<u64>()?;
+    Ok(gas_used)
+}
+
+// sends a tx and returns the json response, which only means that the tx passed CheckTx
 pub async fn send_transaction(rest_url: &str, tx_bytes: Vec<u8>) -> Result<serde_json::Value> {
     let client = Client::new();
 
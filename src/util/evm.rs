@@ -15,4 +15,43 @@ pub struct ContractCallApprovedEvent {
     pub payload_hash: H256,
     pub source_tx_hash: H256,
     pub source_event_index: U256,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, EthEvent)]
+#[ethevent(name = "ContractCallApprovedWithMint", abi = "ContractCallApprovedWithMint(bytes32,string,string,address,bytes32,string,uint256,bytes32,uint256)")]
+pub struct ContractCallApprovedWithMintEvent {
+    #[ethevent(indexed)]
+    pub command_id: H256,
+    pub source_chain: String,
+    pub source_address: String,
+    #[ethevent(indexed)]
+    pub contract_address: Address,
+    #[ethevent(indexed)]
+    pub payload_hash: H256,
+    pub symbol: String,
+    pub amount: U256,
+    pub source_tx_hash: H256,
+    pub source_event_index: U256,
+}
+
+// token minted to the executable alongside a ContractCallApprovedWithMint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovedToken {
+    pub symbol: String,
+    pub amount: U256,
+}
+
+impl ContractCallApprovedWithMintEvent {
+    pub fn split(self) -> (ContractCallApprovedEvent, ApprovedToken) {
+        let event = ContractCallApprovedEvent {
+            command_id: self.command_id,
+            source_chain: self.source_chain,
+            source_address: self.source_address,
+            contract_address: self.contract_address,
+            payload_hash: self.payload_hash,
+            source_tx_hash: self.source_tx_hash,
+            source_event_index: self.source_event_index,
+        };
+        (event, ApprovedToken { symbol: self.symbol, amount: self.amount })
+    }
+}
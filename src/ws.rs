@@ -12,6 +12,9 @@ use tokio_tungstenite::{connect_async, tungstenite::Error as TungsteniteError, t
 use tracing::{error, warn, info, debug};
 use url::Url;
 
+use crate::health;
+use crate::metrics::WS_RECONNECTS;
+
 type MessageHandler = Arc<Mutex<dyn FnMut(String) + Send>>;
 
 pub struct Subscription {
@@ -22,12 +25,21 @@ pub struct Subscription {
 pub struct JSONWebSocketClient {
     url: Url,
     subscriptions: HashMap<String, Subscription>,
+    // health component that is alive while connected and receives a heartbeat on every message
+    health_component: Option<String>,
 }
 
 
 impl JSONWebSocketClient {
     pub fn new(url: Url) -> Self {
-        Self { url, subscriptions: HashMap::new() }
+        Self { url, subscriptions: HashMap::new(), health_component: None }
+    }
+
+    pub fn with_health_check(mut self, name: &str, max_age: Duration) -> Self {
+        health::register(name, Some(max_age));
+        health::set_alive(name, false);
+        self.health_component = Some(name.to_string());
+        self
     }
 
     pub async fn connect(&self) -> tokio_tungstenite::tungstenite::Result<()> {
@@ -37,13 +49,22 @@ impl JSONWebSocketClient {
                 Ok((ws_stream, _)) => {
                     info!("WebSocket connected to {:?}", self.url.to_string());
                     let (write, read) = ws_stream.split();
+                    self.set_connected(true);
                     self.handle_connection(write, read).await;
+                    self.set_connected(false);
                 }
                 Err(e) => error!("Failed to connect: {:?}", e),
             }
 
             warn!("Attempting to reconnect in 10 seconds...");
             sleep(Duration::from_secs(10)).await;
+            WS_RECONNECTS.with_label_values(&[self.url.as_str()]).inc();
+        }
+    }
+
+    fn set_connected(&self, connected: bool) {
+        if let Some(name) = &self.health_component {
+            health::set_alive(name, connected);
         }
     }
 
@@ -84,6 +105,9 @@ impl JSONWebSocketClient {
 
     async fn handle_message(&self, message: Result<Message, TungsteniteError>) -> Result<()> {
         let msg = message.context("Error reading message")?;
+        if let Some(name) = &self.health_component {
+            health::heartbeat(name);
+        }
 
         let text = msg.into_text().context("Failed to convert message to text")?;
 